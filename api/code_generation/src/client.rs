@@ -0,0 +1,296 @@
+//! Generates a reqwest based client for the endpoints in the schema.
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use core_types::{naming::{error_type_name, query_type_name, Convention}, ApiSchema, Endpoint, SchemaType, UriSegment};
+
+/// Generate a `Client` struct with one async method per endpoint, and the
+/// `ClientError` returned when a request fails. Endpoints with declared 
+/// errors return them as `ClientError::Api`.
+///
+/// ```ignore
+/// let client = Client::new("https://example.com");
+/// let user: User = client.get_user(5).await?;
+/// ```
+pub fn parse_client(schema: &ApiSchema) -> TokenStream{
+	let methods = schema.endpoints.iter()
+		.map(|(name,endpoint)|client_method(name, endpoint));
+	let client = syn::Ident::new(schema.codegen.rust_client_name(), Span::call_site());
+
+	// Clients of schemas with a base url can be created without one
+	let default = match schema.codegen.base_url {
+		Some(_) => quote! {
+			impl ::std::default::Default for #client{
+				/// Create a client that sends requests to the [`BASE_URL`].
+				fn default() -> Self{
+					Self::new(BASE_URL)
+				}
+			}
+		},
+		None => TokenStream::new()
+	};
+
+	quote! {
+		/// An error returned by a client request, `E` is the endpoint's
+		/// declared error type.
+		#[derive(Debug)]
+		pub enum ClientError<E = ::std::convert::Infallible>{
+			/// The base url could not be joined with the endpoint's path.
+			Url(::std::string::String),
+			/// The request could not be sent.
+			Request(::reqwest::Error),
+			/// The server rejected the request, or the handler's response
+			/// didn't match the schema, the rejection's code says which.
+			Rejected(::compiler::Rejection),
+			/// The server responded with an error status.
+			Status{
+				status: ::reqwest::StatusCode,
+				body: ::std::string::String
+			},
+			/// The response body did not match the return type.
+			Decode(::reqwest::Error),
+			/// The server responded with one of the endpoint's declared errors.
+			Api(E),
+		}
+
+		impl<E: ::std::fmt::Debug> ::std::fmt::Display for ClientError<E>{
+			fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result{
+				match self {
+					Self::Url(url) => write!(f,"invalid base url: {}",url),
+					Self::Request(err) => write!(f,"request failed: {}",err),
+					Self::Rejected(rejection) => write!(f,"request rejected: {}",rejection),
+					Self::Status{status,body} => write!(f,"server responded with {}: {}",status,body),
+					Self::Decode(err) => write!(f,"failed to decode response: {}",err),
+					Self::Api(err) => write!(f,"server responded with {:?}",err),
+				}
+			}
+		}
+
+		impl<E: ::std::fmt::Debug> ::std::error::Error for ClientError<E>{}
+
+		/// A client for the endpoints in the schema, every request is sent
+		/// with the [`SCHEMA_CHECKSUM`] and [`SCHEMA_VERSION`].
+		#[derive(Debug,Clone)]
+		pub struct #client{
+			base_url: ::std::string::String,
+			http: ::reqwest::Client,
+		}
+
+		#default
+
+		impl #client{
+			/// Create a client that sends requests to `base_url`, e.g.
+			/// `https://example.com/api`.
+			pub fn new(base_url: &str) -> Self{
+				Self::with_client(base_url, ::reqwest::Client::new())
+			}
+
+			/// Create a client that uses an existing [`reqwest::Client`].
+			pub fn with_client(base_url: &str, http: ::reqwest::Client) -> Self{
+				Self{
+					base_url: ::std::string::String::from(base_url),
+					http
+				}
+			}
+
+			/// Append the path segments to the base url, each segment is
+			/// percent encoded.
+			fn url<E>(&self, segments: &[::std::string::String]) -> ::std::result::Result<::reqwest::Url,ClientError<E>>{
+				let mut url = ::reqwest::Url::parse(&self.base_url)
+					.map_err(|_|ClientError::Url(self.base_url.clone()))?;
+				url.path_segments_mut()
+					.map_err(|_|ClientError::Url(self.base_url.clone()))?
+					.pop_if_empty()
+					.extend(segments);
+				Ok(url)
+			}
+
+			#(#methods)*
+		}
+	}
+}
+
+fn client_method(name: &str, endpoint: &Endpoint) -> TokenStream{
+	let ident = syn::Ident::new(&Convention::Rust.member(name), Span::call_site());
+	let method = syn::Ident::new(endpoint.method.as_str(), Span::call_site());
+	let mut params = vec![];
+
+	for param in endpoint.path_params(){
+		let ident = syn::Ident::new(&Convention::Rust.member(param), Span::call_site());
+		let _type = endpoint.params.get(param)
+			.map(SchemaType::parse)
+			.unwrap_or(quote! {::std::string::String});
+		params.push(quote! {#ident: #_type});
+	}
+
+	let error_type = if endpoint.errors.is_empty(){
+		quote! {::std::convert::Infallible}
+	} else {
+		let ident = syn::Ident::new(&error_type_name(name), Span::call_site());
+		quote! {#ident}
+	};
+
+	let mut request = quote! {
+		self.http
+			.request(::reqwest::Method::#method, self.url::<#error_type>(&segments)?)
+			.header(CHECKSUM_HEADER, SCHEMA_CHECKSUM)
+			.header(VERSION_HEADER, SCHEMA_VERSION)
+	};
+
+	let mut query = TokenStream::new();
+	if !endpoint.query.is_empty(){
+		let _type = syn::Ident::new(
+			&query_type_name(name),
+			Span::call_site()
+		);
+		params.push(quote! {query: #_type});
+		query = query_pairs(endpoint);
+		request.extend(quote! {.query(&pairs)});
+	}
+
+	if let Some(input) = endpoint.input.as_ref().filter(|_|endpoint.method.has_request_body()){
+		let _type = input.parse();
+		params.push(quote! {payload: &#_type});
+		request.extend(quote! {.json(payload)});
+	}
+
+	let returns = endpoint.returns.as_ref().filter(|_|endpoint.method.has_response_body());
+	let (return_type,response) = match returns {
+		Some(returns) => {
+			let _type = returns.parse();
+			(
+				_type.clone(),
+				quote! {response.json::<#_type>().await.map_err(ClientError::Decode)}
+			)
+		},
+		None => (quote! {()}, quote! {Ok(())})
+	};
+
+	// Bodies that don't match the declared error fall through to `Status`
+	let arms: Vec<_> = endpoint.error_responses().into_iter().map(|error|{
+		let status = error.status;
+		let _type = error._type.parse();
+		let variant = syn::Ident::new(&error.variant(), Span::call_site());
+		quote! {
+			#status => {
+				if let Ok(body) = ::compiler::serde_json::from_str::<#_type>(&body){
+					return Err(ClientError::Api(#error_type::#variant(body)));
+				}
+			}
+		}
+	}).collect();
+	let errors = if arms.is_empty(){
+		TokenStream::new()
+	} else {
+		quote! {
+			match status.as_u16() {
+				#(#arms)*
+				_ => {}
+			}
+		}
+	};
+
+	let segments = path_segments(endpoint);
+	let doc = format!("`{} {}`",endpoint.method,endpoint.path());
+
+	quote! {
+		#[doc = #doc]
+		pub async fn #ident(&self, #(#params),*) -> ::std::result::Result<#return_type,ClientError<#error_type>>{
+			let segments: ::std::vec::Vec<::std::string::String> = vec![#(#segments),*];
+			#query
+			let response = #request
+				.send()
+				.await
+				.map_err(ClientError::Request)?;
+
+			let status = response.status();
+			if !status.is_success(){
+				let body = response.text().await.unwrap_or_default();
+				if let Some(rejection) = ::compiler::Rejection::parse(&body){
+					return Err(ClientError::Rejected(rejection));
+				}
+				#errors
+				return Err(ClientError::Status{status,body});
+			}
+
+			#response
+		}
+	}
+}
+
+/// The path segments of the endpoint, path parameters are formatted
+/// into the segment they appear in.
+fn path_segments(endpoint: &Endpoint) -> Vec<TokenStream>{
+	let path = endpoint.path();
+	let segments = UriSegment::parse(path)
+		.unwrap_or_else(|_|vec![UriSegment::Literal(path)]);
+
+	// Split on `/` keeping the params in the segment they belong to
+	let mut parts: Vec<Vec<UriSegment>> = vec![vec![]];
+	for segment in segments{
+		match segment {
+			UriSegment::Param(_) => parts.last_mut().unwrap().push(segment),
+			UriSegment::Literal(text) => {
+				let mut pieces = text.split('/');
+				if let Some(first) = pieces.next().filter(|piece|!piece.is_empty()){
+					parts.last_mut().unwrap().push(UriSegment::Literal(first));
+				}
+				for piece in pieces{
+					parts.push(vec![]);
+					if !piece.is_empty(){
+						parts.last_mut().unwrap().push(UriSegment::Literal(piece));
+					}
+				}
+			}
+		}
+	}
+
+	parts.into_iter()
+		.filter(|part|!part.is_empty())
+		.map(|part|{
+			if let [UriSegment::Literal(text)] = part.as_slice(){
+				return quote! {::std::string::String::from(#text)};
+			}
+
+			let mut format = String::new();
+			let mut args = vec![];
+			for segment in part{
+				match segment {
+					UriSegment::Literal(text) => format.push_str(&text.replace('{',"{{").replace('}',"}}")),
+					UriSegment::Param(name) => {
+						format.push_str("{}");
+						args.push(syn::Ident::new(&Convention::Rust.member(name), Span::call_site()));
+					}
+				}
+			}
+			quote! {format!(#format,#(#args),*)}
+		})
+		.collect()
+}
+
+/// Build the `pairs` sent in the query string, missing values are skipped
+/// and every item of a list is sent with the same key.
+fn query_pairs(endpoint: &Endpoint) -> TokenStream{
+	let pushes = endpoint.query.iter().map(|(key,value)|{
+		let ident = syn::Ident::new(&Convention::Rust.member(key), Span::call_site());
+		match value {
+			SchemaType::Optional(_) => quote! {
+				if let Some(value) = &query.#ident{
+					pairs.push((#key,value.to_string()));
+				}
+			},
+			SchemaType::Array(_) => quote! {
+				for value in &query.#ident{
+					pairs.push((#key,value.to_string()));
+				}
+			},
+			_ => quote! {
+				pairs.push((#key,query.#ident.to_string()));
+			}
+		}
+	});
+
+	quote! {
+		let mut pairs: ::std::vec::Vec<(&str,::std::string::String)> = ::std::vec::Vec::new();
+		#(#pushes)*
+	}
+}
@@ -1,7 +1,9 @@
 use proc_macro2::Span;
 use quote::quote;
-use std::{collections::HashMap, fs};
-use core_types::{ApiSchema, SchemaType};
+use core_types::{codegen::RustConfig, naming::{error_type_name, pascal_case, path_type_name, query_type_name, Convention}, validate, ApiSchema, Enum, IndexMap, SchemaType};
+mod router;
+#[cfg(feature = "client")]
+mod client;
 
 /// Generate code from a schema file
 /// ```yaml
@@ -13,37 +15,254 @@ use core_types::{ApiSchema, SchemaType};
 /// ```
 /// Generated code:
 /// ```
+/// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
 /// struct User{
 /// 	id: i32,
 /// 	name: String,
 /// 	email: String
 /// }
 /// ```
+/// 
+/// Names are converted to rust's conventions, snake case for fields and
+/// methods and pascal case for types and variants. Renamed fields and 
+/// variants keep their schema name on the wire
+/// ```yaml
+/// structs:
+///   user_profile:
+///     createdAt: string
+/// ```
+/// Generated code:
+/// ```
+/// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+/// struct UserProfile{
+/// 	#[serde(rename = "createdAt")]
+/// 	created_at: String
+/// }
+/// ```
+/// 
+/// Extra derives and attributes can be added in the `codegen` section, 
+/// the derives are added to every struct and enum while the attributes 
+/// are only added to structs
+/// ```yaml
+/// codegen:
+///   rust:
+///     derives: [Eq]
+///     structs:
+///       User:
+///         derives: [Hash]
+///         attributes:
+///           - doc(hidden)
+/// ```
+/// 
+/// Enums are generated with the serde attributes needed to match the 
+/// wire format
+/// ```yaml
+/// enums:
+///   Status:
+///     - active
+///     - suspended
+///   Shape:
+///     tag: kind
+///     variants:
+///       Circle:
+///         radius: float
+/// ```
+/// Generated code:
+/// ```
+/// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+/// pub enum Status{
+/// 	#[serde(rename = "active")]
+/// 	Active,
+/// 	#[serde(rename = "suspended")]
+/// 	Suspended
+/// }
+/// 
+/// #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+/// #[serde(tag = "kind")]
+/// pub enum Shape{
+/// 	Circle{ radius: f32 }
+/// }
+/// ```
+/// 
+/// Endpoints with path parameters get a struct that can be used with 
+/// axum's `Path` extractor
+/// ```yaml
+/// endpoints:
+///   get_order:
+///     uri: /users/{id}/orders/{order_id}
+///     params:
+///       id: int
+///       order_id: string
+/// ```
+/// Generated code:
+/// ```
+/// #[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+/// pub struct GetOrderPath{
+/// 	pub id: i32,
+/// 	pub order_id: String
+/// }
+/// ```
+/// 
+/// Endpoints with query parameters get a struct that is extracted with
+/// `compiler::Query`, lists are sent as repeated keys, e.g. `tags=a&tags=b`
+/// ```yaml
+/// endpoints:
+///   list_orders:
+///     uri: /orders
+///     query:
+///       page: int?
+///       tags: string[]
+/// ```
+/// Generated code:
+/// ```ignore
+/// #[derive(serde::Deserialize, Debug, Clone, PartialEq)]
+/// pub struct ListOrdersQuery{
+/// 	pub page: Option<i32>,
+/// 	#[serde(default)]
+/// 	pub tags: Vec<String>
+/// }
+/// ```
+/// 
+/// Every endpoint gets a method on the `Api` trait and `router` mounts 
+/// an implementation of it on an axum router, with the `ValidationLayer` 
+/// applied
+/// ```ignore
+/// #[derive(Clone)]
+/// struct Server;
+/// 
+/// impl Api for Server{
+/// 	async fn get_order(&self, path: GetOrderPath) -> Order{
+/// 		todo!()
+/// 	}
+/// }
+/// 
+/// let app = router(Server);
+/// ```
+/// 
+/// With the `client` feature enabled a `Client` is also generated, with
+/// one async method per endpoint. The crate using it has to depend on 
+/// `reqwest` with the `json` feature. The client is renamed with 
+/// `codegen.client_name` and can be created with `Client::default()` 
+/// when the schema has a `codegen.base_url`.
+/// ```ignore
+/// let client = Client::new("https://example.com");
+/// let order: Order = client.get_order(5).await?;
+/// ```
 #[proc_macro]
 pub fn code_gen(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let path = input.to_string().replace("\"", "");
+	// Relative paths are resolved from the crate being compiled, falling
+	// back to the current directory
+	let resolved = std::env::var("CARGO_MANIFEST_DIR")
+		.map(|directory|std::path::Path::new(&directory).join(&path))
+		.ok()
+		.filter(|path|path.exists())
+		.unwrap_or(std::path::PathBuf::from(&path));
 	
-	let contents = fs::read_to_string(path).unwrap();
-    let schema: ApiSchema = serde_yaml::from_str(&contents).unwrap();
+	let (schema,files) = match ApiSchema::load(&resolved) {
+		Ok(schema) => schema,
+		Err(err) => return compile_error(&err.to_string())
+	};
+	if let Err(err) = validate::validate_files(&schema, &files){
+		return compile_error(&format!("invalid schema {}: {}",path,err));
+	}
+
+	let structs = match parse_structs(&schema) {
+		Ok(structs) => structs,
+		Err(err) => return err.to_compile_error().into()
+	};
+	let enums = match parse_enums(&schema) {
+		Ok(enums) => enums,
+		Err(err) => return err.to_compile_error().into()
+	};
+	let path_params = parse_path_params(&schema);
+	let query_params = parse_query_params(&schema);
+	let errors = parse_errors(&schema);
+	let router = router::parse_router(&schema);
+	#[cfg(feature = "client")]
+	let client = client::parse_client(&schema);
+	#[cfg(not(feature = "client"))]
+	let client = quote! {};
 
-	let structs = parse_structs(&schema);
+	let checksum = schema.checksum();
+	let checksum_header = schema.checksum_header();
+	let version_header = schema.version_header();
+	let version = schema.version.to_string();
+	let base_url = schema.codegen.base_url.as_ref().map(|base_url|{
+		let base_url = base_url.trim_end_matches('/');
+		quote! {
+			/// The url that clients send requests to by default.
+			pub const BASE_URL: &str = #base_url;
+		}
+	});
+	// The includes are already merged into the schema
+	let embedded = ApiSchema{include: vec![], ..schema.clone()};
+	let embedded = match serde_yaml::to_string(&embedded) {
+		Ok(embedded) => embedded,
+		Err(err) => return compile_error(&err.to_string())
+	};
 
     let expanded = quote! {
+		/// The checksum of the schema, sent by clients with every request.
+		pub const SCHEMA_CHECKSUM: &str = #checksum;
+		/// The header that the checksum is sent in.
+		pub const CHECKSUM_HEADER: &str = #checksum_header;
+		/// The version of the schema, sent by clients with every request.
+		pub const SCHEMA_VERSION: &str = #version;
+		/// The header that the version is sent in.
+		pub const VERSION_HEADER: &str = #version_header;
+		#base_url
+		/// The schema, with its includes merged in.
+		pub const SCHEMA: &str = #embedded;
+
+		/// Parse the embedded [`SCHEMA`].
+		pub fn schema() -> ::compiler::ApiSchema{
+			::compiler::ApiSchema::parse(SCHEMA).expect("the embedded schema is valid")
+		}
+
 		#structs
+		#enums
+		#path_params
+		#query_params
+		#errors
+		#router
+		#client
     };
 
     proc_macro::TokenStream::from(expanded)
 }
 
-fn parse_structs(schema:&ApiSchema) -> proc_macro2::TokenStream{
+/// Report an error at the macro call site instead of panicking
+fn compile_error(message: &str) -> proc_macro::TokenStream{
+	syn::Error::new(Span::call_site(), message)
+		.to_compile_error()
+		.into()
+}
+
+fn parse_structs(schema:&ApiSchema) -> syn::Result<proc_macro2::TokenStream>{
 	let mut structs = vec![];
+	let config = &schema.codegen.rust;
 	
 	for (key,value) in schema.structs.iter(){
-		let struct_name = syn::Ident::new(key, Span::call_site());
+		let struct_name = syn::Ident::new(&pascal_case(key), Span::call_site());
 		
 		let struct_fields = parse_struct_fields(value);
+		let derives = parse_derives(&config.struct_derives(key))?;
+		let attributes = config.struct_attributes(key)
+			.into_iter()
+			.map(|attribute|{
+				syn::parse_str::<syn::Meta>(attribute).map_err(|err|{
+					syn::Error::new(
+						Span::call_site(),
+						format!("invalid attribute `{}` on {}: {}",attribute,key,err)
+					)
+				})
+			})
+			.collect::<syn::Result<Vec<_>>>()?;
 
 		let _struct = quote! {
+			#derives
+			#(#[#attributes])*
 			pub struct #struct_name{
 				#struct_fields
 			}
@@ -52,16 +271,34 @@ fn parse_structs(schema:&ApiSchema) -> proc_macro2::TokenStream{
 		structs.push(_struct);
 	}
 
-	quote! {#(#structs)*}
+	Ok(quote! {#(#structs)*})
+}
+
+/// The `#[derive(...)]` attribute with the default derives followed by 
+/// the `extra` derives from the schema.
+fn parse_derives(extra: &[&str]) -> syn::Result<proc_macro2::TokenStream>{
+	let derives = RustConfig::DEFAULT_DERIVES.iter()
+		.chain(extra)
+		.map(|derive|{
+			syn::parse_str::<syn::Path>(derive).map_err(|err|{
+				syn::Error::new(Span::call_site(), format!("invalid derive `{}`: {}",derive,err))
+			})
+		})
+		.collect::<syn::Result<Vec<_>>>()?;
+
+	Ok(quote! {#[derive(#(#derives),*)]})
 }
 
-fn parse_struct_fields(fields: &HashMap<String,SchemaType>) -> proc_macro2::TokenStream{
+fn parse_struct_fields(fields: &IndexMap<String,SchemaType>) -> proc_macro2::TokenStream{
 	let mut struct_fields = vec![];
 	for (key,value) in fields.iter(){
-		let field_name = syn::Ident::new(key, Span::call_site());	
+		let (field_name,rename) = member(key);
 		let field_type = value.parse();	
+		let skip = skip_none(value);
 		
 		let field = quote! {
+			#rename
+			#skip
 			pub #field_name: #field_type
 		};
 
@@ -70,3 +307,207 @@ fn parse_struct_fields(fields: &HashMap<String,SchemaType>) -> proc_macro2::Toke
 
 	quote! {#(#struct_fields),*}
 }
+
+/// Optional fields are left out of the body instead of being sent as 
+/// `null`, the typescript types declare them as `name?: T`.
+fn skip_none(_type: &SchemaType) -> Option<proc_macro2::TokenStream>{
+	matches!(_type,SchemaType::Optional(_))
+		.then(||quote! {#[serde(default, skip_serializing_if = "::std::option::Option::is_none")]})
+}
+
+/// The rust name of a field or parameter, with the attribute that renames
+/// it to its schema name on the wire when they differ.
+fn member(name: &str) -> (syn::Ident,Option<proc_macro2::TokenStream>){
+	renamed(name, Convention::Rust.member(name))
+}
+
+/// The rust name of an enum variant, with the attribute that renames it 
+/// to its schema name on the wire when they differ.
+fn variant(name: &str) -> (syn::Ident,Option<proc_macro2::TokenStream>){
+	renamed(name, Convention::Rust.type_name(name))
+}
+
+fn renamed(name: &str, converted: String) -> (syn::Ident,Option<proc_macro2::TokenStream>){
+	let rename = (converted != name).then(||quote! {#[serde(rename = #name)]});
+	(syn::Ident::new(&converted, Span::call_site()),rename)
+}
+
+fn parse_enums(schema:&ApiSchema) -> syn::Result<proc_macro2::TokenStream>{
+	let mut enums = vec![];
+	let derives = parse_derives(&schema.codegen.rust.enum_derives())?;
+
+	for (key,value) in schema.enums.iter(){
+		let enum_name = syn::Ident::new(&pascal_case(key), Span::call_site());
+
+		let _enum = match value {
+			Enum::Simple(variants) => {
+				let variants = variants.iter().map(|name|{
+					let (variant,rename) = variant(name);
+					quote! {#rename #variant}
+				});
+				
+				quote! {
+					#derives
+					pub enum #enum_name{
+						#(#variants),*
+					}
+				}
+			},
+			Enum::Tagged(union) => {
+				let tag = &union.tag;
+				let mut variants = vec![];
+				
+				for (name,fields) in union.variants.iter(){
+					let (variant_name,rename) = variant(name);
+					if fields.is_empty(){
+						variants.push(quote! {#rename #variant_name});
+						continue;
+					}
+
+					let fields = fields.iter().map(|(key,value)|{
+						let (field_name,rename) = member(key);
+						let field_type = value.parse();
+						let skip = skip_none(value);
+						quote! {#rename #skip #field_name: #field_type}
+					});
+					variants.push(quote! {#rename #variant_name{#(#fields),*}});
+				}
+
+				quote! {
+					#derives
+					#[serde(tag = #tag)]
+					pub enum #enum_name{
+						#(#variants),*
+					}
+				}
+			}
+		};
+
+		enums.push(_enum);
+	}
+
+	Ok(quote! {#(#enums)*})
+}
+
+/// Generate a struct for each endpoint's path parameters, named after
+/// the endpoint e.g. `get_user` becomes `GetUserPath`.
+fn parse_path_params(schema:&ApiSchema) -> proc_macro2::TokenStream{
+	let mut structs = vec![];
+
+	for (name,endpoint) in schema.endpoints.iter(){
+		let params = endpoint.path_params();
+		if params.is_empty(){
+			continue;
+		}
+
+		let struct_name = syn::Ident::new(
+			&path_type_name(name), 
+			Span::call_site()
+		);
+
+		let fields = params.iter().map(|param|{
+			let (field_name,rename) = member(param);
+			let field_type = endpoint.params[*param].parse();
+			quote! {#rename pub #field_name: #field_type}
+		});
+
+		structs.push(quote! {
+			#[derive(::serde::Deserialize, Debug, Clone, PartialEq)]
+			pub struct #struct_name{
+				#(#fields),*
+			}
+		});
+	}
+
+	quote! {#(#structs)*}
+}
+
+/// Generate an enum for each endpoint's declared errors, named after the
+/// endpoint e.g. `get_user` becomes `GetUserError`, with one variant per 
+/// status code. Handlers return it as the error and it responds with its 
+/// status code and json body.
+/// 
+/// ```ignore
+/// pub enum GetUserError{
+/// 	NotFound(NotFound),
+/// 	Conflict(Conflict)
+/// }
+/// ```
+fn parse_errors(schema:&ApiSchema) -> proc_macro2::TokenStream{
+	let mut enums = vec![];
+
+	for (name,endpoint) in schema.endpoints.iter(){
+		let errors = endpoint.error_responses();
+		if errors.is_empty(){
+			continue;
+		}
+
+		let enum_name = syn::Ident::new(&error_type_name(name), Span::call_site());
+		let variants: Vec<_> = errors.iter()
+			.map(|error|syn::Ident::new(&error.variant(), Span::call_site()))
+			.collect();
+		let types = errors.iter().map(|error|error._type.parse());
+		let statuses = errors.iter().map(|error|error.status);
+		let docs = errors.iter().map(|error|format!("`{}` {}",error.status,error._type));
+
+		enums.push(quote! {
+			#[derive(Debug, Clone, PartialEq)]
+			pub enum #enum_name{
+				#(
+					#[doc = #docs]
+					#variants(#types)
+				),*
+			}
+
+			impl ::axum::response::IntoResponse for #enum_name{
+				fn into_response(self) -> ::axum::response::Response{
+					match self {
+						#(
+							Self::#variants(body) => ::axum::response::IntoResponse::into_response((
+								::axum::http::StatusCode::from_u16(#statuses)
+									.unwrap_or(::axum::http::StatusCode::INTERNAL_SERVER_ERROR),
+								::axum::Json(body)
+							))
+						),*
+					}
+				}
+			}
+		});
+	}
+
+	quote! {#(#enums)*}
+}
+
+/// Generate a struct for each endpoint's query parameters, named after
+/// the endpoint e.g. `list_users` becomes `ListUsersQuery`.
+fn parse_query_params(schema:&ApiSchema) -> proc_macro2::TokenStream{
+	let mut structs = vec![];
+
+	for (name,endpoint) in schema.endpoints.iter(){
+		if endpoint.query.is_empty(){
+			continue;
+		}
+
+		let struct_name = syn::Ident::new(
+			&query_type_name(name), 
+			Span::call_site()
+		);
+
+		// Lists are sent as repeated keys, which are left out when empty
+		let fields = endpoint.query.iter().map(|(key,value)|{
+			let (field_name,rename) = member(key);
+			let field_type = value.parse();
+			let default = matches!(value,SchemaType::Array(_)).then(||quote! {#[serde(default)]});
+			quote! {#rename #default pub #field_name: #field_type}
+		});
+
+		structs.push(quote! {
+			#[derive(::serde::Deserialize, Debug, Clone, PartialEq)]
+			pub struct #struct_name{
+				#(#fields),*
+			}
+		});
+	}
+
+	quote! {#(#structs)*}
+}
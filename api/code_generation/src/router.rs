@@ -0,0 +1,178 @@
+//! Generates the axum server for the endpoints in the schema.
+use std::collections::BTreeMap;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use core_types::{naming::{error_type_name, pascal_case, path_type_name, query_type_name, Convention}, ApiSchema, Endpoint};
+
+/// Generate the `Api` trait, with one method per endpoint, and a `router`
+/// function that mounts each endpoint on an [`axum::Router`]. 
+/// `router_with_layer` uses a custom layer, e.g. one that also validates
+/// request bodies.
+///
+/// ```ignore
+/// pub trait Api: Clone + Send + Sync + 'static{
+/// 	fn create_user(&self, payload: UserPayload) -> impl Future<Output = User> + Send;
+/// 	// Endpoints with declared errors return a result
+/// 	fn get_user(&self, path: GetUserPath) -> impl Future<Output = Result<User,GetUserError>> + Send;
+/// }
+///
+/// pub fn router<T: Api>(api: T) -> axum::Router{
+/// 	...
+/// }
+/// ```
+pub fn parse_router(schema: &ApiSchema) -> TokenStream{
+	let mut methods = vec![];
+	// Endpoints that share a path have to be mounted on the same route
+	let mut routes: BTreeMap<&str,Vec<TokenStream>> = BTreeMap::new();
+
+	for (name,endpoint) in &schema.endpoints{
+		methods.push(trait_method(name, endpoint));
+		routes.entry(endpoint.path())
+			.or_default()
+			.push(handler(name, endpoint));
+	}
+
+	let core_types::Version{major,minor,patch} = schema.version;
+	let schema_diff = syn::Ident::new(&pascal_case(&schema.schema_diff.to_string()), Span::call_site());
+
+	let routes = routes.into_iter().map(|(path,handlers)|{
+		quote! {
+			.route(#path, ::axum::routing::MethodRouter::new()#(#handlers)*)
+		}
+	});
+
+	quote! {
+		/// The handlers for every endpoint in the schema, mount them with [`router`].
+		pub trait Api: Clone + Send + Sync + 'static{
+			#(#methods)*
+		}
+
+		/// The layer that validates requests against [`SCHEMA_CHECKSUM`] 
+		/// and [`SCHEMA_VERSION`].
+		pub fn validation_layer() -> ::compiler::ValidationLayer{
+			::compiler::ValidationLayer::new(SCHEMA_CHECKSUM)
+				.header(CHECKSUM_HEADER)
+				.version_header(VERSION_HEADER)
+				.version(
+					::compiler::Version::new(#major,#minor,#patch),
+					::compiler::SchemaDiff::#schema_diff
+				)
+		}
+
+		/// Create a router that serves every endpoint in the schema with
+		/// the `api`, requests are validated with the [`validation_layer`].
+		pub fn router<T: Api>(api: T) -> ::axum::Router{
+			router_with_layer(api, validation_layer())
+		}
+
+		/// Create a router that serves every endpoint in the schema with
+		/// the `api`, requests are validated with the `layer`.
+		/// 
+		/// ```ignore
+		/// let layer = validation_layer().validate_requests(schema());
+		/// let app = router_with_layer(api, layer);
+		/// ```
+		pub fn router_with_layer<T: Api>(api: T, layer: ::compiler::ValidationLayer) -> ::axum::Router{
+			::axum::Router::new()
+				#(#routes)*
+				.with_state(api)
+				.layer(layer)
+		}
+	}
+}
+
+/// An argument to an endpoint handler, e.g. `Path(path): Path<GetUserPath>`.
+struct Extractor{
+	name: syn::Ident,
+	/// The path of the extractor, e.g. `::axum::extract::Path`
+	extractor: TokenStream,
+	/// The type being extracted
+	_type: TokenStream
+}
+
+/// The extractors for an endpoint, in the order they are passed to the 
+/// trait method.
+fn extractors(name: &str, endpoint: &Endpoint) -> Vec<Extractor>{
+	let mut extractors = vec![];
+
+	if !endpoint.path_params().is_empty(){
+		let _type = syn::Ident::new(&path_type_name(name), Span::call_site());
+		extractors.push(Extractor{
+			name: syn::Ident::new("path", Span::call_site()),
+			extractor: quote! {::axum::extract::Path},
+			_type: quote! {#_type}
+		});
+	}
+
+	if !endpoint.query.is_empty(){
+		let _type = syn::Ident::new(&query_type_name(name), Span::call_site());
+		extractors.push(Extractor{
+			name: syn::Ident::new("query", Span::call_site()),
+			extractor: quote! {::compiler::Query},
+			_type: quote! {#_type}
+		});
+	}
+
+	if let Some(input) = endpoint.input.as_ref().filter(|_|endpoint.method.has_request_body()){
+		extractors.push(Extractor{
+			name: syn::Ident::new("payload", Span::call_site()),
+			extractor: quote! {::axum::Json},
+			_type: input.parse()
+		});
+	}
+
+	extractors
+}
+
+fn trait_method(name: &str, endpoint: &Endpoint) -> TokenStream{
+	let ident = syn::Ident::new(&Convention::Rust.member(name), Span::call_site());
+	let params = extractors(name, endpoint)
+		.into_iter()
+		.map(|Extractor { name, _type, .. }|quote! {#name: #_type});
+	let mut returns = match endpoint.returns.as_ref().filter(|_|endpoint.method.has_response_body()) {
+		Some(returns) => returns.parse(),
+		None => quote! {()}
+	};
+	if !endpoint.errors.is_empty(){
+		let error = syn::Ident::new(&error_type_name(name), Span::call_site());
+		returns = quote! {::std::result::Result<#returns,#error>};
+	}
+
+	let doc = format!("`{} {}`",endpoint.method,endpoint.path());
+	quote! {
+		#[doc = #doc]
+		fn #ident(&self, #(#params),*) -> impl ::std::future::Future<Output = #returns> + Send;
+	}
+}
+
+fn handler(name: &str, endpoint: &Endpoint) -> TokenStream{
+	let ident = syn::Ident::new(&Convention::Rust.member(name), Span::call_site());
+	let method = syn::Ident::new(endpoint.method.as_str(), Span::call_site());
+	let extractors = extractors(name, endpoint);
+	let patterns = extractors.iter()
+		.map(|Extractor { name, extractor, _type }|quote! {#extractor(#name): #extractor<#_type>});
+	let args: Vec<_> = extractors.iter().map(|extractor|&extractor.name).collect();
+
+	let has_body = endpoint.returns.is_some() && endpoint.method.has_response_body();
+	let response = match (has_body,endpoint.errors.is_empty()) {
+		(true,true) => quote! {::axum::Json(api.#ident(#(#args),*).await)},
+		(false,true) => quote! {
+			api.#ident(#(#args),*).await;
+			::axum::http::StatusCode::NO_CONTENT
+		},
+		// The error enum responds with its own status
+		(true,false) => quote! {api.#ident(#(#args),*).await.map(::axum::Json)},
+		(false,false) => quote! {
+			api.#ident(#(#args),*).await.map(|_|::axum::http::StatusCode::NO_CONTENT)
+		}
+	};
+
+	quote! {
+		.on(
+			::axum::routing::MethodFilter::#method,
+			|::axum::extract::State(api): ::axum::extract::State<T>, #(#patterns),*| async move {
+				#response
+			}
+		)
+	}
+}
@@ -0,0 +1,760 @@
+//! Compare two versions of a schema and classify the changes.
+//!
+//! Each change is classified by who it breaks:
+//!
+//! - **clients**: a client generated from the old schema fails against a
+//!   server using the new schema.
+//! - **servers**: a client generated from the new schema fails against a
+//!   server using the old schema.
+//!
+//! Changes that break clients need a major version bump and changes that
+//! only break servers, like adding an endpoint, need a minor version bump.
+//! Since they change the generated code, additions need at least a minor
+//! bump and removals a major bump.
+//!
+//! ```
+//! use core_types::{ApiSchema,SchemaDiff};
+//! use compiler::diff;
+//!
+//! let old = ApiSchema::parse("version: 1.0.0\nschema_diff: none\nstructs: {}\nendpoints: {}").unwrap();
+//! let new = ApiSchema::parse("version: 1.0.1\nschema_diff: none\nstructs:\n  User:\n    id: int\nendpoints: {}").unwrap();
+//!
+//! let changes = diff::diff(&old, &new);
+//! assert_eq!(changes.required_bump(),SchemaDiff::Minor);
+//! assert!(changes.check(&old, &new).is_err());
+//! ```
+use std::collections::{BTreeSet, HashMap, HashSet};
+use core_types::{ApiSchema, Endpoint, Enum, IndexMap, SchemaDiff, SchemaType, Version};
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum ChangeKind{
+	Added,
+	Removed,
+	Changed
+}
+
+/// A single difference between two schemas.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Change{
+	pub kind: ChangeKind,
+	/// The path of the item that changed, e.g. `structs.User.name`
+	pub path: String,
+	pub message: String,
+	/// Clients using the old schema fail against servers using the new one
+	pub breaks_clients: bool,
+	/// Clients using the new schema fail against servers using the old one
+	pub breaks_servers: bool
+}
+
+impl Change{
+	pub fn is_breaking(&self) -> bool{
+		self.breaks_clients || self.breaks_servers
+	}
+
+	/// The smallest version bump that this change needs.
+	pub fn required_bump(&self) -> SchemaDiff{
+		if self.breaks_clients || self.kind == ChangeKind::Removed{
+			SchemaDiff::Major
+		} else if self.breaks_servers || self.kind == ChangeKind::Added{
+			SchemaDiff::Minor
+		} else {
+			SchemaDiff::Patch
+		}
+	}
+}
+
+impl std::fmt::Display for Change{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f,"{}: {}",self.path,self.message)?;
+		match (self.breaks_clients,self.breaks_servers) {
+			(true,true) => write!(f," (breaks clients and servers)"),
+			(true,false) => write!(f," (breaks clients)"),
+			(false,true) => write!(f," (breaks servers)"),
+			(false,false) => Ok(())
+		}
+	}
+}
+
+/// The changes between two schemas, sorted by path.
+#[derive(Debug,Clone,PartialEq,Eq,Default)]
+pub struct SchemaChanges{
+	pub changes: Vec<Change>
+}
+
+impl SchemaChanges{
+	pub fn is_empty(&self) -> bool{
+		self.changes.is_empty()
+	}
+
+	pub fn is_breaking(&self) -> bool{
+		self.changes.iter().any(Change::is_breaking)
+	}
+
+	/// The smallest version bump that all the changes need.
+	pub fn required_bump(&self) -> SchemaDiff{
+		self.changes.iter()
+			.map(Change::required_bump)
+			.max()
+			.unwrap_or(SchemaDiff::None)
+	}
+
+	/// Check that the version of the `new` schema was bumped enough for
+	/// the changes, and that the new server doesn't accept old clients
+	/// that the changes break.
+	pub fn check(&self, old: &ApiSchema, new: &ApiSchema) -> Result<(),Vec<VersionError>>{
+		if self.is_empty(){
+			return Ok(());
+		}
+
+		if new.version <= old.version{
+			return Err(vec![VersionError::NotIncreased { old: old.version, new: new.version }]);
+		}
+
+		let mut errors = vec![];
+		let declared = old.version.diff(&new.version);
+		let required = self.required_bump();
+		if declared < required{
+			errors.push(VersionError::InsufficientBump {
+				required,
+				old: old.version,
+				new: new.version
+			});
+		}
+
+		// The new server accepts the old clients
+		if new.schema_diff.allows(&new.version, &old.version){
+			for change in self.changes.iter().filter(|change|change.breaks_clients){
+				errors.push(VersionError::IncompatibleClients {
+					schema_diff: new.schema_diff,
+					old: old.version,
+					path: change.path.clone()
+				});
+			}
+		}
+
+		if errors.is_empty(){
+			Ok(())
+		} else {
+			Err(errors)
+		}
+	}
+}
+
+/// The version of a changed schema doesn't match its changes.
+#[derive(Debug,Clone,PartialEq,Eq,thiserror::Error)]
+pub enum VersionError{
+	#[error("the schema changed but the version was not increased ({old} -> {new})")]
+	NotIncreased{
+		old: Version,
+		new: Version
+	},
+	#[error("the changes need a {required} version bump ({old} -> {new})")]
+	InsufficientBump{
+		required: SchemaDiff,
+		old: Version,
+		new: Version
+	},
+	#[error("{path} breaks {old} clients, which are still accepted with schema_diff: {schema_diff}")]
+	IncompatibleClients{
+		schema_diff: SchemaDiff,
+		old: Version,
+		path: String
+	}
+}
+
+/// Compare two schemas.
+pub fn diff(old: &ApiSchema, new: &ApiSchema) -> SchemaChanges{
+	let mut differ = Differ{
+		usage: Usage::new(old).merge(Usage::new(new)),
+		changes: vec![]
+	};
+
+	let headers = [
+		("checksum_header",old.checksum_header(),new.checksum_header()),
+		("version_header",old.version_header(),new.version_header()),
+	];
+	for (path,old,new) in headers{
+		if old != new{
+			differ.push(Change {
+				kind: ChangeKind::Changed,
+				path: String::from(path),
+				message: format!("changed from `{}` to `{}`",old,new),
+				breaks_clients: true,
+				breaks_servers: true
+			});
+		}
+	}
+
+	differ.diff_types(old, new);
+	differ.diff_endpoints(old, new);
+
+	let mut changes = differ.changes;
+	changes.sort_by(|a,b|a.path.cmp(&b.path));
+	SchemaChanges { changes }
+}
+
+/// Whether data read with one schema and written with the other would
+/// be rejected.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+struct Strictness{
+	/// Readers using the new schema reject data written with the old one
+	new_stricter: bool,
+	/// Readers using the old schema reject data written with the new one
+	old_stricter: bool
+}
+
+impl Strictness{
+	const NONE: Self = Self { new_stricter: false, old_stricter: false };
+	const NEW: Self = Self { new_stricter: true, old_stricter: false };
+	const OLD: Self = Self { new_stricter: false, old_stricter: true };
+	const BOTH: Self = Self { new_stricter: true, old_stricter: true };
+
+	/// Compare two types of the same value.
+	fn of_types(old: &SchemaType, new: &SchemaType) -> Self{
+		match (old,new) {
+			_ if old == new => Self::NONE,
+			// The new writer can send null
+			(old,SchemaType::Optional(inner)) if inner.as_ref() == old => Self::OLD,
+			// The old writer can send null
+			(SchemaType::Optional(inner),new) if inner.as_ref() == new => Self::NEW,
+			_ => Self::BOTH
+		}
+	}
+}
+
+/// The direction that a type is sent in.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+struct Direction{
+	/// Sent from clients to servers
+	input: bool,
+	/// Sent from servers to clients
+	output: bool
+}
+
+impl Direction{
+	const INPUT: Self = Self { input: true, output: false };
+	const OUTPUT: Self = Self { input: false, output: true };
+
+	/// Returns who the change breaks as `(clients,servers)`.
+	///
+	/// Servers read the input, so an old client breaks if the new reader
+	/// is stricter. Clients read the output, so an old client breaks if
+	/// the old reader is stricter.
+	fn breaks(&self, strictness: Strictness) -> (bool,bool){
+		let mut clients = false;
+		let mut servers = false;
+		if self.input{
+			clients |= strictness.new_stricter;
+			servers |= strictness.old_stricter;
+		}
+		if self.output{
+			clients |= strictness.old_stricter;
+			servers |= strictness.new_stricter;
+		}
+		(clients,servers)
+	}
+}
+
+/// The types that are sent in each direction, including the types they
+/// reference.
+#[derive(Debug,Default)]
+struct Usage{
+	inputs: HashSet<String>,
+	outputs: HashSet<String>
+}
+
+impl Usage{
+	fn new(schema: &ApiSchema) -> Self{
+		let mut usage = Self::default();
+		for endpoint in schema.endpoints.values(){
+			if let Some(input) = &endpoint.input{
+				reference(schema, input, &mut usage.inputs);
+			}
+			if let Some(returns) = &endpoint.returns{
+				reference(schema, returns, &mut usage.outputs);
+			}
+			for _type in endpoint.errors.values(){
+				reference(schema, _type, &mut usage.outputs);
+			}
+		}
+		usage
+	}
+
+	fn merge(mut self, other: Self) -> Self{
+		self.inputs.extend(other.inputs);
+		self.outputs.extend(other.outputs);
+		self
+	}
+
+	fn direction(&self, name: &str) -> Direction{
+		Direction {
+			input: self.inputs.contains(name),
+			output: self.outputs.contains(name)
+		}
+	}
+}
+
+/// Add the struct or enum the type refers to, and every type it
+/// references, to `names`.
+fn reference(schema: &ApiSchema, _type: &SchemaType, names: &mut HashSet<String>){
+	let name = match _type {
+		SchemaType::Array(inner) |
+		SchemaType::Optional(inner) |
+		SchemaType::Map(inner) => return reference(schema, inner, names),
+		SchemaType::Struct(name) => name,
+		_ => return
+	};
+
+	if !names.insert(name.clone()){
+		return;
+	}
+
+	if let Some(fields) = schema.structs.get(name){
+		for field in fields.values(){
+			reference(schema, field, names);
+		}
+	}
+
+	if let Some(Enum::Tagged(union)) = schema.enums.get(name){
+		for field in union.variants.values().flat_map(|fields|fields.values()){
+			reference(schema, field, names);
+		}
+	}
+}
+
+/// A named type in the schema.
+#[derive(Debug,Clone,Copy,PartialEq)]
+enum TypeDef<'a>{
+	Struct(&'a IndexMap<String,SchemaType>),
+	Enum(&'a Enum)
+}
+
+impl<'a> TypeDef<'a>{
+	fn all(schema: &'a ApiSchema) -> HashMap<&'a str,Self>{
+		let structs = schema.structs.iter()
+			.map(|(name,fields)|(name.as_str(),Self::Struct(fields)));
+		let enums = schema.enums.iter()
+			.map(|(name,value)|(name.as_str(),Self::Enum(value)));
+		structs.chain(enums).collect()
+	}
+
+	fn section(&self) -> &'static str{
+		match self {
+			Self::Struct(_) => "structs",
+			Self::Enum(_) => "enums"
+		}
+	}
+}
+
+struct Differ{
+	usage: Usage,
+	changes: Vec<Change>
+}
+
+impl Differ{
+	fn push(&mut self, change: Change){
+		self.changes.push(change);
+	}
+
+	/// Record a change that affects data sent in the `direction`.
+	fn change(
+		&mut self,
+		kind: ChangeKind,
+		path: String,
+		message: String,
+		direction: Direction,
+		strictness: Strictness
+	){
+		let (breaks_clients,breaks_servers) = direction.breaks(strictness);
+		self.push(Change { kind, path, message, breaks_clients, breaks_servers });
+	}
+
+	fn diff_types(&mut self, old: &ApiSchema, new: &ApiSchema){
+		let old_types = TypeDef::all(old);
+		let new_types = TypeDef::all(new);
+		let names: BTreeSet<&str> = old_types.keys().chain(new_types.keys()).copied().collect();
+
+		for name in names{
+			let direction = self.usage.direction(name);
+			match (old_types.get(name),new_types.get(name)) {
+				(Some(old),None) => {
+					let path = format!("{}.{name}",old.section());
+					self.change(ChangeKind::Removed, path, format!("removed `{name}`"), direction, Strictness::BOTH);
+				},
+				(None,Some(new)) => {
+					let path = format!("{}.{name}",new.section());
+					self.change(ChangeKind::Added, path, format!("added `{name}`"), direction, Strictness::NONE);
+				},
+				(Some(TypeDef::Struct(old)),Some(TypeDef::Struct(new))) => {
+					self.diff_fields(&format!("structs.{name}"), old, new, direction);
+				},
+				(Some(TypeDef::Enum(old)),Some(TypeDef::Enum(new))) => {
+					self.diff_enum(name, old, new, direction);
+				},
+				(Some(old),Some(new)) => {
+					let path = format!("{}.{name}",new.section());
+					let message = format!(
+						"changed `{name}` from {} to {}",
+						old.section().trim_end_matches('s'),
+						new.section().trim_end_matches('s')
+					);
+					self.change(ChangeKind::Changed, path, message, direction, Strictness::BOTH);
+				},
+				(None,None) => {}
+			}
+		}
+	}
+
+	/// Compare the fields of a struct, a tagged variant or query parameters.
+	fn diff_fields(
+		&mut self,
+		path: &str,
+		old: &IndexMap<String,SchemaType>,
+		new: &IndexMap<String,SchemaType>,
+		direction: Direction
+	){
+		let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+		for name in names{
+			let path = format!("{path}.{name}");
+			match (old.get(name),new.get(name)) {
+				(Some(old),None) => {
+					let strictness = if is_optional(old) { Strictness::NONE } else { Strictness::OLD };
+					let message = format!("removed field `{name}`");
+					self.change(ChangeKind::Removed, path, message, direction, strictness);
+				},
+				(None,Some(new)) => {
+					let strictness = if is_optional(new) { Strictness::NONE } else { Strictness::NEW };
+					let message = format!("added field `{name}: {new}`");
+					self.change(ChangeKind::Added, path, message, direction, strictness);
+				},
+				(Some(old),Some(new)) if old != new => {
+					let message = format!("changed type from `{old}` to `{new}`");
+					self.change(ChangeKind::Changed, path, message, direction, Strictness::of_types(old, new));
+				},
+				_ => {}
+			}
+		}
+	}
+
+	fn diff_enum(&mut self, name: &str, old: &Enum, new: &Enum, direction: Direction){
+		let path = format!("enums.{name}");
+		match (old,new) {
+			(Enum::Simple(old),Enum::Simple(new)) => {
+				for value in old.iter().filter(|value|!new.contains(value)){
+					let message = format!("removed value `{value}`");
+					self.change(ChangeKind::Removed, format!("{path}.{value}"), message, direction, Strictness::NEW);
+				}
+				for value in new.iter().filter(|value|!old.contains(value)){
+					let message = format!("added value `{value}`");
+					self.change(ChangeKind::Added, format!("{path}.{value}"), message, direction, Strictness::OLD);
+				}
+			},
+			(Enum::Tagged(old),Enum::Tagged(new)) => {
+				if old.tag != new.tag{
+					let message = format!("changed tag from `{}` to `{}`",old.tag,new.tag);
+					self.change(ChangeKind::Changed, format!("{path}.tag"), message, direction, Strictness::BOTH);
+				}
+
+				let variants: BTreeSet<&String> = old.variants.keys().chain(new.variants.keys()).collect();
+				for variant in variants{
+					let path = format!("{path}.variants.{variant}");
+					match (old.variants.get(variant),new.variants.get(variant)) {
+						(Some(_),None) => {
+							let message = format!("removed variant `{variant}`");
+							self.change(ChangeKind::Removed, path, message, direction, Strictness::NEW);
+						},
+						(None,Some(_)) => {
+							let message = format!("added variant `{variant}`");
+							self.change(ChangeKind::Added, path, message, direction, Strictness::OLD);
+						},
+						(Some(old),Some(new)) => self.diff_fields(&path, old, new, direction),
+						(None,None) => {}
+					}
+				}
+			},
+			_ => {
+				let message = String::from("changed between a list of values and a tagged union");
+				self.change(ChangeKind::Changed, path, message, direction, Strictness::BOTH);
+			}
+		}
+	}
+
+	fn diff_endpoints(&mut self, old: &ApiSchema, new: &ApiSchema){
+		let names: BTreeSet<&String> = old.endpoints.keys().chain(new.endpoints.keys()).collect();
+		for name in names{
+			let path = format!("endpoints.{name}");
+			match (old.endpoints.get(name),new.endpoints.get(name)) {
+				(Some(_),None) => self.push(Change {
+					kind: ChangeKind::Removed,
+					path,
+					message: format!("removed endpoint `{name}`"),
+					breaks_clients: true,
+					breaks_servers: false
+				}),
+				(None,Some(_)) => self.push(Change {
+					kind: ChangeKind::Added,
+					path,
+					message: format!("added endpoint `{name}`"),
+					breaks_clients: false,
+					breaks_servers: true
+				}),
+				(Some(old),Some(new)) => self.diff_endpoint(&path, old, new),
+				(None,None) => {}
+			}
+		}
+	}
+
+	fn diff_endpoint(&mut self, path: &str, old: &Endpoint, new: &Endpoint){
+		if old.method != new.method{
+			let message = format!("changed method from {} to {}",old.method,new.method);
+			self.change(ChangeKind::Changed, format!("{path}.method"), message, Direction::INPUT, Strictness::BOTH);
+		}
+
+		if old.uri != new.uri{
+			// Only the path is part of the route, the host can change
+			// between deployments
+			let strictness = if old.path() == new.path() { Strictness::NONE } else { Strictness::BOTH };
+			let message = format!("changed uri from `{}` to `{}`",old.uri,new.uri);
+			self.change(ChangeKind::Changed, format!("{path}.uri"), message, Direction::INPUT, strictness);
+		}
+
+		for (name,old_type) in &old.params{
+			if let Some(new_type) = new.params.get(name)
+				&& old_type != new_type
+			{
+				let message = format!("changed type from `{old_type}` to `{new_type}`");
+				let strictness = Strictness::of_types(old_type, new_type);
+				self.change(ChangeKind::Changed, format!("{path}.params.{name}"), message, Direction::INPUT, strictness);
+			}
+		}
+
+		self.diff_fields(&format!("{path}.query"), &old.query, &new.query, Direction::INPUT);
+		self.diff_body(&format!("{path}.input"), old.input.as_ref(), new.input.as_ref(), Direction::INPUT);
+		self.diff_body(&format!("{path}.returns"), old.returns.as_ref(), new.returns.as_ref(), Direction::OUTPUT);
+		self.diff_errors(&format!("{path}.errors"), old, new);
+	}
+
+	/// Compare the declared errors, clients treat undeclared errors as a 
+	/// plain error status so they can be added and removed.
+	fn diff_errors(&mut self, path: &str, old: &Endpoint, new: &Endpoint){
+		let statuses: BTreeSet<&u16> = old.errors.keys().chain(new.errors.keys()).collect();
+		for status in statuses{
+			let path = format!("{path}.{status}");
+			match (old.errors.get(status),new.errors.get(status)) {
+				(Some(old),None) => {
+					let message = format!("removed `{status}: {old}`");
+					self.change(ChangeKind::Removed, path, message, Direction::OUTPUT, Strictness::NONE);
+				},
+				(None,Some(new)) => {
+					let message = format!("added `{status}: {new}`");
+					self.change(ChangeKind::Added, path, message, Direction::OUTPUT, Strictness::NONE);
+				},
+				(Some(old),Some(new)) if old != new => {
+					let message = format!("changed type from `{old}` to `{new}`");
+					self.change(ChangeKind::Changed, path, message, Direction::OUTPUT, Strictness::of_types(old, new));
+				},
+				_ => {}
+			}
+		}
+	}
+
+	/// Compare the input or return type of an endpoint.
+	fn diff_body(
+		&mut self,
+		path: &str,
+		old: Option<&SchemaType>,
+		new: Option<&SchemaType>,
+		direction: Direction
+	){
+		let path = String::from(path);
+		match (old,new) {
+			(Some(old),None) => {
+				let message = format!("removed `{old}`");
+				self.change(ChangeKind::Removed, path, message, direction, Strictness::OLD);
+			},
+			(None,Some(new)) => {
+				let message = format!("added `{new}`");
+				self.change(ChangeKind::Added, path, message, direction, Strictness::NEW);
+			},
+			(Some(old),Some(new)) if old != new => {
+				let message = format!("changed type from `{old}` to `{new}`");
+				self.change(ChangeKind::Changed, path, message, direction, Strictness::of_types(old, new));
+			},
+			_ => {}
+		}
+	}
+}
+
+/// Fields that can be left out, query lists default to being empty.
+fn is_optional(_type: &SchemaType) -> bool{
+	matches!(_type,SchemaType::Optional(_) | SchemaType::Array(_))
+}
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	const OLD: &str = concat!(
+		"version: 1.2.0\n",
+		"schema_diff: minor\n",
+		"structs:\n",
+		"  User:\n",
+		"    id: int\n",
+		"    name: string\n",
+		"  UserPayload:\n",
+		"    name: string\n",
+		"enums:\n",
+		"  Status:\n",
+		"    - active\n",
+		"endpoints:\n",
+		"  get_user:\n",
+		"    uri: https://example.com/users/{id}\n",
+		"    method: GET\n",
+		"    params:\n",
+		"      id: int\n",
+		"    returns: User\n",
+		"  create_user:\n",
+		"    uri: https://example.com/users\n",
+		"    method: POST\n",
+		"    input: UserPayload\n",
+		"    returns: User\n",
+	);
+
+	/// The old schema with the text replaced
+	fn changed(from: &str, to: &str) -> ApiSchema{
+		assert!(OLD.contains(from));
+		ApiSchema::parse(&OLD.replacen(from, to, 1)).unwrap()
+	}
+
+	fn changes(new: &ApiSchema) -> Vec<(String,bool,bool)>{
+		let old = ApiSchema::parse(OLD).unwrap();
+		diff(&old, new).changes
+			.into_iter()
+			.map(|change|(change.path,change.breaks_clients,change.breaks_servers))
+			.collect()
+	}
+
+	#[test]
+	fn unchanged_schema(){
+		let old = ApiSchema::parse(OLD).unwrap();
+		let changes = diff(&old, &old);
+
+		assert!(changes.is_empty());
+		assert_eq!(changes.required_bump(),SchemaDiff::None);
+		assert!(changes.check(&old, &old).is_ok());
+	}
+
+	#[test]
+	fn output_fields(){
+		// Old clients ignore the new field, new clients need it
+		let new = changed("    name: string\n  UserPayload", "    name: string\n    email: string\n  UserPayload");
+		assert_eq!(changes(&new),vec![("structs.User.email".to_string(),false,true)]);
+
+		// Old clients might not get the field
+		let new = changed("    name: string\n  UserPayload", "    name: string?\n  UserPayload");
+		assert_eq!(changes(&new),vec![("structs.User.name".to_string(),true,false)]);
+	}
+
+	#[test]
+	fn input_fields(){
+		// Old clients don't send the new field
+		let new = changed("  UserPayload:\n    name: string\n", "  UserPayload:\n    name: string\n    email: string\n");
+		assert_eq!(changes(&new),vec![("structs.UserPayload.email".to_string(),true,false)]);
+
+		// Optional fields can be added
+		let new = changed("  UserPayload:\n    name: string\n", "  UserPayload:\n    name: string\n    email: string?\n");
+		assert_eq!(changes(&new),vec![("structs.UserPayload.email".to_string(),false,false)]);
+
+		// New clients don't send the removed field
+		let new = changed("  UserPayload:\n    name: string\n", "  UserPayload:\n    nickname: string?\n");
+		assert_eq!(changes(&new),vec![
+			("structs.UserPayload.name".to_string(),false,true),
+			("structs.UserPayload.nickname".to_string(),false,false),
+		]);
+	}
+
+	#[test]
+	fn endpoints(){
+		let new = changed("    method: POST\n", "    method: PUT\n");
+		assert_eq!(changes(&new),vec![("endpoints.create_user.method".to_string(),true,true)]);
+
+		let new = changed("endpoints:\n", "endpoints:\n  delete_user:\n    uri: /users/{id}\n    method: DELETE\n    params:\n      id: int\n");
+		assert_eq!(changes(&new),vec![("endpoints.delete_user".to_string(),false,true)]);
+
+		let new = changed("https://example.com/users/{id}", "https://api.example.com/users/{id}");
+		assert_eq!(changes(&new),vec![("endpoints.get_user.uri".to_string(),false,false)]);
+
+		let new = changed("      id: int\n", "      id: string\n");
+		assert_eq!(changes(&new),vec![("endpoints.get_user.params.id".to_string(),true,true)]);
+	}
+
+	#[test]
+	fn endpoint_errors(){
+		let new = changed("    returns: User\n", "    returns: User\n    errors:\n      404: string\n");
+		assert_eq!(changes(&new),vec![("endpoints.get_user.errors.404".to_string(),false,false)]);
+
+		let old = new;
+		let new = ApiSchema::parse(&OLD.replacen("    returns: User\n", "    returns: User\n    errors:\n      404: User\n", 1)).unwrap();
+		let changes: Vec<_> = diff(&old, &new).changes.into_iter().map(|change|change.path).collect();
+		assert_eq!(changes,vec!["endpoints.get_user.errors.404"]);
+	}
+
+	#[test]
+	fn enum_values(){
+		let new = changed("    - active\n", "    - active\n    - suspended\n");
+		let old = ApiSchema::parse(OLD).unwrap();
+		let changes = diff(&old, &new);
+
+		// Unused types don't break clients or servers
+		assert_eq!(changes.changes.len(),1);
+		assert_eq!(changes.changes[0].path,"enums.Status.suspended");
+		assert!(!changes.is_breaking());
+		assert_eq!(changes.required_bump(),SchemaDiff::Minor);
+	}
+
+	#[test]
+	fn check_version_bump(){
+		let old = ApiSchema::parse(OLD).unwrap();
+
+		// Adding an endpoint is a minor change
+		let new = changed("endpoints:\n", "endpoints:\n  delete_user:\n    uri: /users/{id}\n    method: DELETE\n    params:\n      id: int\n");
+		let changes = diff(&old, &new);
+		assert_eq!(
+			changes.check(&old, &new),
+			Err(vec![VersionError::NotIncreased { old: Version::new(1, 2, 0), new: Version::new(1, 2, 0) }])
+		);
+
+		let mut patch = new.clone();
+		patch.version = Version::new(1, 2, 1);
+		assert_eq!(
+			changes.check(&old, &patch),
+			Err(vec![VersionError::InsufficientBump {
+				required: SchemaDiff::Minor,
+				old: Version::new(1, 2, 0),
+				new: Version::new(1, 2, 1)
+			}])
+		);
+
+		let mut minor = new.clone();
+		minor.version = Version::new(1, 3, 0);
+		assert_eq!(changes.check(&old, &minor),Ok(()));
+	}
+
+	#[test]
+	fn check_schema_diff_policy(){
+		let old = ApiSchema::parse(OLD).unwrap();
+		let mut new = changed("      id: int\n", "      id: string\n");
+		new.version = Version::new(2, 0, 0);
+		let changes = diff(&old, &new);
+		assert_eq!(changes.required_bump(),SchemaDiff::Major);
+		assert_eq!(changes.check(&old, &new),Ok(()));
+
+		// The server would still accept the broken 1.2.0 clients
+		new.schema_diff = SchemaDiff::Major;
+		let errors = changes.check(&old, &new).unwrap_err();
+		assert_eq!(
+			errors[0].to_string(),
+			"endpoints.get_user.params.id breaks 1.2.0 clients, which are still accepted with schema_diff: major"
+		);
+	}
+}
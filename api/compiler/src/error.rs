@@ -8,5 +8,12 @@ pub enum Error {
 	#[error(transparent)]
 	Io(#[from] std::io::Error),
 	#[error(transparent)]
-	SerdeYaml(#[from] serde_yaml::Error)
+	SerdeYaml(#[from] serde_yaml::Error),
+	#[error(transparent)]
+	Load(#[from] core_types::LoadError),
+	#[error(transparent)]
+	Validation(#[from] core_types::validate::ValidationError),
+	/// The schema has no output file for the language
+	#[error("the schema has no codegen.{0}.output")]
+	MissingOutput(String)
 }
\ No newline at end of file
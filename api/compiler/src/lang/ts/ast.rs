@@ -0,0 +1,495 @@
+//! The statements and expressions of the generated typescript.
+//!
+//! ```
+//! use compiler::ts::{Expr, Stmt};
+//!
+//! let statement = Stmt::Const{
+//! 	name: String::from("body"),
+//! 	_type: None,
+//! 	value: Expr::ident("response").member("json").call(vec![]).await_()
+//! };
+//! assert_eq!(statement.to_string(),"const body = await response.json();");
+//! ```
+use super::{printer::{block, concat, group, if_break, indent, join, list, print, print_flat, text, Doc}, Field, TsType};
+
+/// A typescript expression
+#[derive(Debug,Clone,PartialEq, Eq, PartialOrd, Ord)]
+pub enum Expr{
+	/// An identifier or keyword, `response`, `this` or `null`
+	Ident(String),
+	/// A string literal, `"POST"`
+	String(String),
+	/// A number literal, `404`
+	Number(String),
+	/// A template literal, `` `/users/${id}` ``
+	Template(Vec<TemplatePart>),
+	/// `object.property`
+	Member(Box<Expr>,String),
+	/// `object?.property`
+	OptionalMember(Box<Expr>,String),
+	/// `callee(args)`
+	Call(Box<Expr>,Vec<Expr>),
+	/// `new Class<T>(args)`
+	New{
+		class: String,
+		type_args: Vec<TsType>,
+		args: Vec<Expr>
+	},
+	/// `await value`
+	Await(Box<Expr>),
+	/// `left op right`
+	Binary(Box<Expr>,&'static str,Box<Expr>),
+	/// `condition ? then : otherwise`
+	Conditional(Box<Expr>,Box<Expr>,Box<Expr>),
+	/// `target = value`
+	Assign(Box<Expr>,Box<Expr>),
+	/// An object literal, always written over multiple lines
+	Object(Vec<Property>),
+	/// `[a, b]`
+	Array(Vec<Expr>),
+	/// `(param: T) => body`
+	Arrow(Vec<Param>,Box<Expr>)
+}
+
+/// A piece of a template literal
+#[derive(Debug,Clone,PartialEq, Eq, PartialOrd, Ord)]
+pub enum TemplatePart{
+	Text(String),
+	Expr(Expr)
+}
+
+/// A property of an object literal
+#[derive(Debug,Clone,PartialEq, Eq, PartialOrd, Ord)]
+pub struct Property{
+	pub key: Key,
+	pub value: Expr
+}
+
+/// The key of an object literal property
+#[derive(Debug,Clone,PartialEq, Eq, PartialOrd, Ord)]
+pub enum Key{
+	/// `name: value`
+	Name(String),
+	/// `[expr]: value`
+	Computed(Expr),
+	/// `...value`, the properties of the value
+	Spread
+}
+
+/// A parameter of an arrow function, the pattern can destructure the
+/// value e.g. `[key, value]`
+#[derive(Debug,Clone,PartialEq, Eq, PartialOrd, Ord)]
+pub struct Param{
+	pub pattern: String,
+	pub _type: TsType
+}
+
+impl Param{
+	pub fn new(pattern: &str, _type: TsType) -> Self{
+		Self { pattern: String::from(pattern), _type }
+	}
+}
+
+/// A typescript statement
+#[derive(Debug,Clone,PartialEq, Eq, PartialOrd, Ord)]
+pub enum Stmt{
+	/// `const name: T = value;`
+	Const{
+		name: String,
+		_type: Option<TsType>,
+		value: Expr
+	},
+	/// `value;`
+	Expr(Expr),
+	/// `return value;`
+	Return(Option<Expr>),
+	/// `throw value;`
+	Throw(Expr),
+	/// `if (condition) { ... }`
+	If{
+		condition: Expr,
+		then: Vec<Stmt>
+	},
+	/// `switch (value) { case ...: ... }`
+	Switch{
+		value: Expr,
+		cases: Vec<(Expr,Vec<Stmt>)>,
+		default: Vec<Stmt>
+	}
+}
+
+/// Operator precedence, higher binds tighter.
+fn precedence(operator: &str) -> u8{
+	match operator {
+		"||" | "??" => 1,
+		"&&" => 2,
+		"==" | "!=" | "===" | "!==" => 3,
+		"<" | ">" | "<=" | ">=" => 4,
+		"+" | "-" => 5,
+		_ => 6
+	}
+}
+
+impl Expr{
+	pub fn ident(name: &str) -> Self{
+		Self::Ident(String::from(name))
+	}
+
+	pub fn string(value: &str) -> Self{
+		Self::String(String::from(value))
+	}
+
+	pub fn number(value: impl std::fmt::Display) -> Self{
+		Self::Number(value.to_string())
+	}
+
+	pub fn member(self, property: &str) -> Self{
+		Self::Member(Box::new(self), String::from(property))
+	}
+
+	pub fn optional_member(self, property: &str) -> Self{
+		Self::OptionalMember(Box::new(self), String::from(property))
+	}
+
+	pub fn call(self, args: Vec<Expr>) -> Self{
+		Self::Call(Box::new(self), args)
+	}
+
+	/// `await self`, named with a trailing underscore because `await` is a
+	/// keyword
+	pub fn await_(self) -> Self{
+		Self::Await(Box::new(self))
+	}
+
+	pub fn binary(self, operator: &'static str, right: Expr) -> Self{
+		Self::Binary(Box::new(self), operator, Box::new(right))
+	}
+
+	pub fn conditional(self, then: Expr, otherwise: Expr) -> Self{
+		Self::Conditional(Box::new(self), Box::new(then), Box::new(otherwise))
+	}
+
+	pub fn assign(self, value: Expr) -> Self{
+		Self::Assign(Box::new(self), Box::new(value))
+	}
+
+	pub fn new_(class: &str, type_args: Vec<TsType>, args: Vec<Expr>) -> Self{
+		Self::New { class: String::from(class), type_args, args }
+	}
+
+	/// An object literal with named keys.
+	pub fn object<'a>(properties: impl IntoIterator<Item = (&'a str,Expr)>) -> Self{
+		Self::Object(properties.into_iter()
+			.map(|(key,value)|Property{key: Key::Name(String::from(key)), value})
+			.collect())
+	}
+
+	pub fn arrow(params: Vec<Param>, body: Expr) -> Self{
+		Self::Arrow(params, Box::new(body))
+	}
+
+	/// Objects and callbacks as the last argument keep their opening on the
+	/// same line as the call.
+	fn is_hugged(&self) -> bool{
+		match self {
+			Self::Object(properties) => !properties.is_empty(),
+			Self::Arrow(..) => true,
+			_ => false
+		}
+	}
+
+	/// This expression as an operand, wrapped in parentheses if it binds
+	/// looser than `precedence`.
+	fn operand(&self, precedence: u8) -> Doc{
+		let own = match self {
+			Self::Binary(_,operator,_) => self::precedence(operator),
+			Self::Conditional(..)|Self::Assign(..)|Self::Arrow(..) => 0,
+			Self::Await(_) => 7,
+			_ => u8::MAX
+		};
+
+		if own < precedence{
+			concat([text("("),self.to_doc(),text(")")])
+		} else {
+			self.to_doc()
+		}
+	}
+
+	pub(crate) fn to_doc(&self) -> Doc{
+		match self {
+			Self::Ident(name) => text(name),
+			Self::String(value) => text(string_literal(value)),
+			Self::Number(value) => text(value),
+			Self::Template(parts) => {
+				let mut docs = vec![text("`")];
+				for part in parts{
+					match part {
+						TemplatePart::Text(value) => docs.push(text(escape_template(value))),
+						TemplatePart::Expr(expr) => docs.push(text(format!("${{{}}}",print_flat(&expr.to_doc()))))
+					}
+				}
+				docs.push(text("`"));
+				concat(docs)
+			},
+			Self::Member(object,property) => concat([object.operand(u8::MAX),text("."),text(property)]),
+			Self::OptionalMember(object,property) => concat([object.operand(u8::MAX),text("?."),text(property)]),
+			Self::Call(callee,args) => concat([callee.operand(u8::MAX),arguments(args)]),
+			Self::New { class, type_args, args } => {
+				let mut docs = vec![text("new "),text(class)];
+				if !type_args.is_empty(){
+					let type_args: Vec<String> = type_args.iter().map(TsType::to_string).collect();
+					docs.push(text(format!("<{}>",type_args.join(", "))));
+				}
+				docs.push(arguments(args));
+				concat(docs)
+			},
+			Self::Await(value) => concat([text("await "),value.operand(7)]),
+			Self::Binary(left,operator,right) => {
+				let precedence = self::precedence(operator);
+				group(concat([
+					left.operand(precedence),
+					text(format!(" {operator}")),
+					indent(concat([Doc::Line,right.operand(precedence + 1)]))
+				]))
+			},
+			Self::Conditional(condition,then,otherwise) => group(concat([
+				condition.operand(1),
+				indent(concat([
+					Doc::Line,
+					text("? "),
+					then.operand(1),
+					Doc::Line,
+					text(": "),
+					otherwise.operand(1)
+				]))
+			])),
+			Self::Assign(target,value) => concat([target.to_doc(),text(" = "),value.to_doc()]),
+			Self::Object(properties) => {
+				let properties = properties.iter()
+					.map(|property|concat([property.to_doc(),text(",")]))
+					.collect();
+				block(properties)
+			},
+			Self::Array(items) => list("[", items.iter().map(Expr::to_doc).collect(), "]"),
+			Self::Arrow(params,body) => {
+				let params: Vec<String> = params.iter()
+					.map(|param|format!("{}: {}",param.pattern,param._type))
+					.collect();
+				concat([text(format!("({}) => ",params.join(", "))),body.operand(1)])
+			}
+		}
+	}
+}
+
+/// Call arguments, an object or callback as the last argument is hugged
+/// by the parentheses instead of breaking every argument.
+fn arguments(args: &[Expr]) -> Doc{
+	match args.split_last() {
+		Some((last,rest)) if last.is_hugged() && rest.iter().all(|arg|!arg.is_hugged()) => {
+			let mut docs: Vec<Doc> = rest.iter()
+				.map(|arg|concat([arg.to_doc(),text(", ")]))
+				.collect();
+			docs.push(last.to_doc());
+			concat([text("("),concat(docs),text(")")])
+		},
+		_ => list("(", args.iter().map(Expr::to_doc).collect(), ")")
+	}
+}
+
+/// A quoted string, in single quotes if that needs fewer escapes.
+fn string_literal(value: &str) -> String{
+	let quoted = format!("{:?}",value);
+	if value.contains('"') && !value.contains('\''){
+		return format!("'{}'",quoted[1..quoted.len() - 1].replace("\\\"", "\""));
+	}
+	quoted
+}
+
+fn escape_template(value: &str) -> String{
+	value.replace('\\', "\\\\")
+		.replace('`', "\\`")
+		.replace("${", "\\${")
+}
+
+impl Property{
+	fn to_doc(&self) -> Doc{
+		let key = match &self.key {
+			Key::Name(name) => text(name),
+			Key::Computed(expr) => concat([text("["),expr.to_doc(),text("]")]),
+			Key::Spread => return concat([text("..."),self.value.operand(1)])
+		};
+		concat([key,text(":"),assignment(&self.value)])
+	}
+}
+
+/// The right hand side of an assignment or property, conditions and
+/// operations that don't fit go on the next line.
+fn assignment(value: &Expr) -> Doc{
+	match value {
+		Expr::Binary(..)|Expr::Conditional(..) => group(indent(concat([Doc::Line,value.to_doc()]))),
+		_ => concat([text(" "),value.to_doc()])
+	}
+}
+
+impl Stmt{
+	pub(crate) fn to_doc(&self) -> Doc{
+		match self {
+			Self::Const { name, _type, value } => {
+				let declaration = match _type {
+					Some(_type) => format!("const {name}: {_type} ="),
+					None => format!("const {name} =")
+				};
+				concat([text(declaration),assignment(value),text(";")])
+			},
+			Self::Expr(expr) => concat([expr.to_doc(),text(";")]),
+			Self::Return(None) => text("return;"),
+			Self::Return(Some(value)) => concat([text("return "),value.to_doc(),text(";")]),
+			Self::Throw(value) => concat([text("throw "),value.to_doc(),text(";")]),
+			Self::If { condition, then } => concat([
+				text("if ("),
+				condition.to_doc(),
+				text(") "),
+				statements(then)
+			]),
+			Self::Switch { value, cases, default } => {
+				let mut clauses: Vec<Doc> = cases.iter()
+					.map(|(case,body)|clause(concat([text("case "),case.to_doc(),text(":")]), body))
+					.collect();
+				if !default.is_empty(){
+					clauses.push(clause(text("default:"), default));
+				}
+				concat([text("switch ("),value.to_doc(),text(") "),block(clauses)])
+			}
+		}
+	}
+}
+
+/// A `case` or `default` clause of a switch.
+fn clause(label: Doc, body: &[Stmt]) -> Doc{
+	let body = body.iter().map(|stmt|concat([Doc::HardLine,stmt.to_doc()]));
+	concat([label,indent(concat(body))])
+}
+
+/// The statements in a block.
+pub(crate) fn statements(body: &[Stmt]) -> Doc{
+	block(body.iter().map(Stmt::to_doc).collect())
+}
+
+/// A top level function
+///
+/// ```typescript
+/// export function decodeUser(value: any): User {
+/// 	...
+/// }
+/// ```
+#[derive(Debug,Clone,PartialEq, Eq, PartialOrd, Ord)]
+pub struct Function{
+	pub exported: bool,
+	pub name: String,
+	pub params: Vec<Field>,
+	pub returns: TsType,
+	pub body: Vec<Stmt>
+}
+
+impl Function{
+	pub(crate) fn to_doc(&self) -> Doc{
+		let keyword = if self.exported {"export function "} else {"function "};
+		concat([
+			text(keyword),
+			text(&self.name),
+			list("(", self.params.iter().map(|param|text(param.to_string())).collect(), ")"),
+			text(format!(": {} ",self.returns)),
+			statements(&self.body)
+		])
+	}
+}
+
+impl std::fmt::Display for Expr{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&print(&self.to_doc()))
+	}
+}
+
+impl std::fmt::Display for Stmt{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&print(&self.to_doc()))
+	}
+}
+
+impl std::fmt::Display for Function{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&print(&self.to_doc()))
+	}
+}
+
+/// Wraps a multi-line union type so the members line up, used for type
+/// aliases and fields.
+pub(crate) fn union_members(members: Vec<Doc>) -> Doc{
+	group(indent(concat([
+		Doc::Line,
+		if_break(text("| "), text("")),
+		join(members, concat([Doc::Line,text("| ")]))
+	])))
+}
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	#[test]
+	fn parentheses(){
+		let sum = Expr::ident("a").binary("+", Expr::ident("b"));
+		assert_eq!(sum.clone().member("length").to_string(),"(a + b).length");
+		assert_eq!(Expr::ident("c").binary("==", sum).to_string(),"c == a + b");
+
+		let response = Expr::ident("fetch").call(vec![]).await_();
+		assert_eq!(response.member("ok").to_string(),"(await fetch()).ok");
+
+		let options = Expr::ident("options").optional_member("baseUrl");
+		assert_eq!(options.binary("??", Expr::string("")).to_string(),"options?.baseUrl ?? \"\"");
+	}
+
+	#[test]
+	fn hugged_object(){
+		let call = Expr::ident("fetch").call(vec![
+			Expr::ident("url"),
+			Expr::object([("method",Expr::string("POST"))])
+		]);
+		assert_eq!(call.to_string(),"fetch(url, {\n\tmethod: \"POST\",\n})");
+	}
+
+	#[test]
+	fn long_conditional(){
+		let value = Expr::ident("value").member("manager");
+		let conditional = value.clone()
+			.binary("==", Expr::ident("null"))
+			.conditional(value.clone(), Expr::ident("decodeUser").call(vec![value]));
+		let object = Expr::Object(vec![Property{
+			key: Key::Name(String::from("managerOfTheDepartment")),
+			value: conditional
+		}]);
+
+		assert_eq!(object.to_string(),concat!(
+			"{\n",
+			"\tmanagerOfTheDepartment:\n",
+			"\t\tvalue.manager == null ? value.manager : decodeUser(value.manager),\n",
+			"}",
+		));
+	}
+
+	#[test]
+	fn template_escapes(){
+		let template = Expr::Template(vec![
+			TemplatePart::Text(String::from("/a`b/")),
+			TemplatePart::Expr(Expr::ident("id"))
+		]);
+		assert_eq!(template.to_string(),"`/a\\`b/${id}`");
+	}
+
+	#[test]
+	fn string_quotes(){
+		assert_eq!(Expr::string("POST").to_string(),"\"POST\"");
+		assert_eq!(Expr::string("one of \"a\"").to_string(),"'one of \"a\"'");
+		assert_eq!(Expr::string("it's \"a\"").to_string(),"\"it's \\\"a\\\"\"");
+	}
+}
@@ -1,4 +1,4 @@
-use super::{Field,Method};
+use super::{printer::{block, concat, print, text, Doc}, Field, Method};
 
 /// A typescript class
 #[derive(Debug,Clone,PartialEq, Eq, PartialOrd, Ord)]
@@ -27,19 +27,27 @@ impl Class{
 	}
 }
 
-impl std::fmt::Display for Class{
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		writeln!(f,"export class {} {{",self.name)?;
-
-		for field in &self.fields{
-			writeln!(f,"\t{}",field)?;
-		}
+impl Class{
+	/// The fields followed by the methods, separated by blank lines.
+	pub(crate) fn to_doc(&self) -> Doc{
+		let mut lines: Vec<Doc> = self.fields.iter()
+			.map(|field|text(format!("{};",field)))
+			.collect();
 
 		for method in &self.methods{
-			writeln!(f,"\t{}",method)?;
+			if !lines.is_empty(){
+				lines.push(text(""));
+			}
+			lines.push(method.to_doc());
 		}
 
-		write!(f,"}}")
+		concat([text(format!("export class {} ",self.name)),block(lines)])
+	}
+}
+
+impl std::fmt::Display for Class{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&print(&self.to_doc()))
 	}
 }
 
@@ -67,15 +75,16 @@ mod tests{
 		class.push_method(username);
 
 		let output = concat!(
-			"class User {\n",
-			"\tid: number\n",
-			"\temail: string\n",
-			"\tuser_name: string\n",
-			"\tid(): number {n\n",
+			"export class User {\n",
+			"\tid: number;\n",
+			"\temail: string;\n",
+			"\tuser_name: string;\n",
+			"\n",
+			"\tid(): number {}\n",
 			"\n",
+			"\tuser_name(): string {}\n",
 			"}",
 		);
-		println!("{}",&class);
-		todo!()
+		assert_eq!(format!("{}",class),output);
 	}
 }
\ No newline at end of file
@@ -0,0 +1,231 @@
+//! Converting values between the wire format, which uses the schema's
+//! names, and the generated types, which use typescript's conventions.
+//!
+//! ```typescript
+//! export function decodeUser(value: any): User {
+//! 	return {
+//! 		createdAt: value.created_at,
+//! 		manager: value.manager == null ? value.manager : decodeUser(value.manager),
+//! 	};
+//! }
+//! ```
+use core_types::{naming::{pascal_case, Convention}, Enum, IndexMap, SchemaType};
+use super::{ast::{Expr, Function, Key, Param, Property, Stmt}, Field, TsType};
+
+/// Which way a value is converted.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Direction{
+	/// From the wire format to the generated types
+	Decode,
+	/// From the generated types to the wire format
+	Encode
+}
+
+impl Direction{
+	/// The name of the function converting the type, e.g. `decodeUser`.
+	pub fn function_name(&self, type_name: &str) -> String{
+		match self {
+			Self::Decode => format!("decode{}",pascal_case(type_name)),
+			Self::Encode => format!("encode{}",pascal_case(type_name))
+		}
+	}
+
+	/// The key that a field is read from and the key it's written to.
+	fn keys(&self, name: &str) -> (String,String){
+		let converted = Convention::Typescript.member(name);
+		match self {
+			Self::Decode => (String::from(name),converted),
+			Self::Encode => (converted,String::from(name))
+		}
+	}
+}
+
+/// An expression converting `value` to or from the `_type`, `None` if the
+/// type is the same in both formats.
+pub fn convert(direction: Direction, _type: &SchemaType, value: Expr) -> Option<Expr>{
+	convert_nested(direction, _type, value, 0)
+}
+
+/// The `depth` keeps the names of nested callback parameters unique.
+fn convert_nested(direction: Direction, _type: &SchemaType, value: Expr, depth: usize) -> Option<Expr>{
+	match _type {
+		SchemaType::Int|
+		SchemaType::Float|
+		SchemaType::String|
+		SchemaType::Boolean => None,
+		SchemaType::Struct(name) => Some(Expr::ident(&direction.function_name(name)).call(vec![value])),
+		SchemaType::Optional(inner) => {
+			let converted = convert_nested(direction, inner, value.clone(), depth)?;
+			Some(value.clone().binary("==", Expr::ident("null")).conditional(value, converted))
+		},
+		SchemaType::Array(inner) => {
+			let item = format!("item{depth}");
+			let converted = convert_nested(direction, inner, Expr::ident(&item), depth + 1)?;
+			let callback = Expr::arrow(vec![Param::new(&item, TsType::Any)], converted);
+			Some(value.member("map").call(vec![callback]))
+		},
+		SchemaType::Map(inner) => {
+			let (key,entry) = (format!("key{depth}"),format!("value{depth}"));
+			let converted = convert_nested(direction, inner, Expr::ident(&entry), depth + 1)?;
+			let callback = Expr::arrow(
+				vec![Param::new(&format!("[{key}, {entry}]"), TsType::Tuple(vec![TsType::String,TsType::Any]))],
+				Expr::Array(vec![Expr::ident(&key),converted])
+			);
+			let entries = Expr::ident("Object").member("entries").call(vec![value]);
+			Some(Expr::ident("Object").member("fromEntries").call(vec![
+				entries.member("map").call(vec![callback])
+			]))
+		}
+	}
+}
+
+/// The object literal properties converting each field of `value`.
+fn object_properties(direction: Direction, fields: &IndexMap<String,SchemaType>) -> Vec<Property>{
+	fields.iter()
+		.map(|(name,_type)|{
+			let (from,to) = direction.keys(name);
+			let value = Expr::ident("value").member(&from);
+			Property{
+				key: Key::Name(to),
+				value: convert(direction, _type, value.clone()).unwrap_or(value)
+			}
+		})
+		.collect()
+}
+
+/// A converter function with its signature filled in.
+fn converter(direction: Direction, name: &str, body: Vec<Stmt>) -> Function{
+	let type_name = TsType::Custom(pascal_case(name));
+	let (param,returns) = match direction {
+		Direction::Decode => (TsType::Any,type_name),
+		Direction::Encode => (type_name,TsType::Any)
+	};
+	Function{
+		exported: true,
+		name: direction.function_name(name),
+		params: vec![Field::new("value", param)],
+		returns,
+		body
+	}
+}
+
+/// The decode and encode functions of a struct.
+pub fn struct_converters(name: &str, fields: &IndexMap<String,SchemaType>) -> [Function;2]{
+	[Direction::Decode,Direction::Encode].map(|direction|{
+		let object = Expr::Object(object_properties(direction, fields));
+		converter(direction, name, vec![Stmt::Return(Some(object))])
+	})
+}
+
+/// The decode and encode functions of an enum, the values of simple
+/// enums are the same in both formats.
+pub fn enum_converters(name: &str, value: &Enum) -> [Function;2]{
+	[Direction::Decode,Direction::Encode].map(|direction|{
+		let returned = Stmt::Return(Some(Expr::ident("value")));
+		let body = match value {
+			Enum::Simple(_) => vec![returned],
+			Enum::Tagged(union) => {
+				let (from,to) = direction.keys(&union.tag);
+				let cases = union.variants.iter()
+					.map(|(variant,fields)|{
+						let tag = Property{key: Key::Name(to.clone()), value: Expr::string(variant)};
+						let properties = std::iter::once(tag)
+							.chain(object_properties(direction, fields))
+							.collect();
+						(Expr::string(variant),vec![Stmt::Return(Some(Expr::Object(properties)))])
+					})
+					.collect();
+				vec![Stmt::Switch{
+					value: Expr::ident("value").member(&from),
+					cases,
+					default: vec![returned]
+				}]
+			}
+		};
+		converter(direction, name, body)
+	})
+}
+
+#[cfg(test)]
+mod tests{
+	use core_types::TaggedUnion;
+	use super::*;
+
+	#[test]
+	fn nested_conversion(){
+		let _type: SchemaType = "map<string, User[]>".parse().unwrap();
+		let value = Expr::ident("value").member("teams");
+		assert_eq!(
+			convert(Direction::Decode, &_type, value).unwrap().to_string(),
+			concat!(
+				"Object.fromEntries(\n",
+				"\tObject.entries(value.teams).map(([key0, value0]: [string, any]) => [\n",
+				"\t\tkey0,\n",
+				"\t\tvalue0.map((item1: any) => decodeUser(item1)),\n",
+				"\t]),\n",
+				")",
+			)
+		);
+
+		let _type: SchemaType = "User?".parse().unwrap();
+		let value = Expr::ident("value").member("manager");
+		assert_eq!(
+			convert(Direction::Encode, &_type, value).unwrap().to_string(),
+			"value.manager == null ? value.manager : encodeUser(value.manager)"
+		);
+
+		let _type: SchemaType = "string[]".parse().unwrap();
+		assert_eq!(convert(Direction::Decode, &_type, Expr::ident("tags")),None);
+	}
+
+	#[test]
+	fn struct_functions(){
+		let fields = IndexMap::from([
+			("created_at".to_string(),SchemaType::String),
+			("manager".to_string(),SchemaType::Optional(Box::new(SchemaType::Struct("user".to_string())))),
+		]);
+		let [decode,encode] = struct_converters("user", &fields);
+
+		assert_eq!(decode.to_string(),concat!(
+			"export function decodeUser(value: any): User {\n",
+			"\treturn {\n",
+			"\t\tcreatedAt: value.created_at,\n",
+			"\t\tmanager: value.manager == null ? value.manager : decodeUser(value.manager),\n",
+			"\t};\n",
+			"}",
+		));
+		assert_eq!(encode.to_string(),concat!(
+			"export function encodeUser(value: User): any {\n",
+			"\treturn {\n",
+			"\t\tcreated_at: value.createdAt,\n",
+			"\t\tmanager: value.manager == null ? value.manager : encodeUser(value.manager),\n",
+			"\t};\n",
+			"}",
+		));
+	}
+
+	#[test]
+	fn tagged_enum_functions(){
+		let shape = Enum::Tagged(TaggedUnion{
+			tag: "shape_kind".to_string(),
+			variants: IndexMap::from([
+				("Circle".to_string(),IndexMap::from([("radius".to_string(),SchemaType::Float)])),
+			])
+		});
+		let [decode,_] = enum_converters("shape", &shape);
+
+		assert_eq!(decode.to_string(),concat!(
+			"export function decodeShape(value: any): Shape {\n",
+			"\tswitch (value.shape_kind) {\n",
+			"\t\tcase \"Circle\":\n",
+			"\t\t\treturn {\n",
+			"\t\t\t\tshapeKind: \"Circle\",\n",
+			"\t\t\t\tradius: value.radius,\n",
+			"\t\t\t};\n",
+			"\t\tdefault:\n",
+			"\t\t\treturn value;\n",
+			"\t}\n",
+			"}",
+		));
+	}
+}
@@ -1,7 +1,13 @@
-use core_types::{Endpoint, HttpMethod};
-use proc_macro2::TokenStream;
+use core_types::{naming::{error_type_name, query_type_name, Convention}, Endpoint, HttpMethod, SchemaType, UriSegment};
 
-use super::{Field, TsType};
+use super::{
+	ast::{statements, Expr, Key, Param, Property, Stmt, TemplatePart},
+	convert::{convert, Direction},
+	printer::{concat, list, print, text, Doc},
+	validator::check,
+	Field,
+	TsType
+};
 
 // TODO add jsdoc
 /// A method on a class
@@ -11,7 +17,7 @@ pub struct Method{
 	identifier: String,
 	parameters: Vec<Field>,
 	returns: Option<TsType>,
-	body: String,
+	body: Vec<Stmt>,
 }
 
 impl Method{
@@ -21,7 +27,7 @@ impl Method{
 	}
 	
 	/// Get a reference to the method body.
-	pub fn body(&self) -> &str{
+	pub fn body(&self) -> &[Stmt]{
 		&self.body
 	}
 	
@@ -39,37 +45,45 @@ impl Method{
 	}
 }
 
-impl std::fmt::Display for Method{
-	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl Method{
+	pub(crate) fn to_doc(&self) -> Doc{
+		let mut signature = String::new();
 		if self.is_async{
-			write!(f,"async ")?;
-		}
-		write!(f,"{}",self.identifier)?;
-		write!(f,"(")?;
-		
-		for param in &self.parameters{
-			write!(f,"{},",param)?;
-		}
-		
-		write!(f,")")?;
-		match &self.returns {
-			Some(_type) => {
-				if self.is_async{
-					writeln!(f,": Promise<{}> {{",_type)?;
-				}else {
-					writeln!(f,": {} {{",_type)?;
-				}
-			},
-			None =>{
-				write!(f," {{")?
-			}
+			signature.push_str("async ");
 		}
-		
-		write!(f,"\t\t{}",self.body)?;
-		write!(f,"\n\t}}")
+		signature.push_str(&self.identifier);
+
+		let returns = match &self.returns {
+			Some(_type) if self.is_async => format!(": Promise<{}> ",_type),
+			Some(_type) => format!(": {} ",_type),
+			None => String::from(" ")
+		};
+
+		concat([
+			text(signature),
+			list("(", self.parameters.iter().map(|param|text(param.to_string())).collect(), ")"),
+			text(returns),
+			statements(&self.body)
+		])
+	}
+}
+
+impl std::fmt::Display for Method{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&print(&self.to_doc()))
 	}
 }
 
+/// Options for the methods created from endpoints.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct EndpointOptions{
+	/// The schema has a base url, which the client's `baseUrl` defaults to,
+	/// otherwise it defaults to the host in the endpoint's uri
+	pub base_url: bool,
+	/// Check the payload against the schema before sending it
+	pub validate_requests: bool
+}
+
 /// Builder pattern for [`Method`]
 /// 
 /// # Example
@@ -89,9 +103,7 @@ impl std::fmt::Display for Method{
 /// ```typescript
 /// // Dummy class 
 /// class World{
-/// 	async is_player_alive(player_id: number): Promise<boolean>{
-/// 
-/// 	}
+/// 	async is_player_alive(player_id: number): Promise<boolean> {}
 /// }
 /// ```
 #[derive(Debug,Clone)]
@@ -100,7 +112,7 @@ pub struct MethodBuilder{
 	identifier: String,
 	parameters: Vec<Field>,
 	returns: Option<TsType>,
-	body: String
+	body: Vec<Stmt>
 }
 
 impl MethodBuilder{
@@ -123,9 +135,7 @@ impl MethodBuilder{
 	/// ```typescript
 	/// // Dummy class 
 	/// class World{
-	/// 	async is_player_alive(player_id: number): Promise<boolean>{
-	/// 
-	/// 	}
+	/// 	async is_player_alive(player_id: number): Promise<boolean> {}
 	/// }
 	/// ```
 	pub fn new(identifier: &str) -> Self{
@@ -134,7 +144,7 @@ impl MethodBuilder{
 			is_async: false,
 			parameters: vec![],
 			returns: None,
-			body: String::new()
+			body: vec![]
 		}
 	}
 
@@ -156,9 +166,9 @@ impl MethodBuilder{
 		self
 	}
 
-	/// Add a parameter to the method
-	pub fn body(mut self, body: &str) -> Self{
-		self.body = String::from(body);
+	/// Set the statements in the method's body
+	pub fn body(mut self, body: Vec<Stmt>) -> Self{
+		self.body = body;
 		self
 	}
 
@@ -175,99 +185,240 @@ impl MethodBuilder{
 	}
 
 	/// Create a new method from an [`Endpoint`].
+	/// 
+	/// Path parameters in the uri are added as parameters before the 
+	/// payload and are url encoded into the path. Query parameters are
+	/// passed as a single `query` object and serialized into the query 
+	/// string. Endpoints without an input don't take a payload and 
+	/// endpoints without a return type return `Promise<void>`. Declared
+	/// errors are thrown as an `ApiError`. The method and its parameters 
+	/// are camel case, payloads and responses are converted to and from 
+	/// the wire format. Responses are checked against the schema and throw
+	/// a `ValidationError` if they don't match.
 	pub fn from_endpoint(name:&str,endpoint: &Endpoint) -> Method{
-		let input_type:TsType = endpoint.input.clone().into();
-		let return_type:TsType = endpoint.returns.clone().into();
+		Self::from_endpoint_with_options(name, endpoint, EndpointOptions::default())
+	}
+
+	/// Create a new method from an [`Endpoint`] of a schema with a base 
+	/// url, the request is sent to the client's `baseUrl`.
+	pub fn from_endpoint_with_base_url(name:&str,endpoint: &Endpoint) -> Method{
+		let options = EndpointOptions{base_url: true, ..Default::default()};
+		Self::from_endpoint_with_options(name, endpoint, options)
+	}
+
+	/// Create a new method from an [`Endpoint`] with the `options`.
+	pub fn from_endpoint_with_options(name:&str,endpoint: &Endpoint,options: EndpointOptions) -> Method{
 		let http_method = endpoint.method;
-		let uri = endpoint.uri.clone();
-		
-		let method_body = quote::quote!{
-			try{
-				const response = await fetch(#uri,{
-					headers:{
-						"Api-Checksum": this.checksum
-					},
-					method: #http_method,
-					body: JSON.stringify(payload)
-				});
-				if (response.ok){
-					const body: #return_type = await response.json();
-					return body;
-				}
-				else{
-					const error = await response.json();
-					throw error;
+		let input_type = endpoint.input.as_ref()
+			.filter(|_|http_method.has_request_body())
+			.map(TsType::from);
+		let return_type = endpoint.returns.as_ref()
+			.filter(|_|http_method.has_response_body())
+			.map(TsType::from)
+			.unwrap_or(TsType::Void);
+		let has_query = !endpoint.query.is_empty();
+		let response = Expr::ident("response");
+		let mut body = vec![];
+
+		let mut url = Self::url_parts(endpoint, options.base_url);
+		if has_query{
+			body.extend(Self::query_string(endpoint));
+			let search = Expr::ident("search");
+			url.push(TemplatePart::Expr(search.clone().conditional(
+				Expr::string("?").binary("+", search),
+				Expr::string("")
+			)));
+		}
+		body.push(Stmt::Const{
+			name: String::from("url"),
+			_type: None,
+			value: Expr::Template(url)
+		});
+
+		// Fetch defaults to GET so the method is left out
+		// The default headers can't replace the schema headers
+		let headers = Expr::Object(vec![
+			Property{
+				key: Key::Spread,
+				value: Expr::ident("this").member("headers")
+			},
+			Property{
+				key: Key::Computed(Expr::ident("CHECKSUM_HEADER")),
+				value: Expr::ident("this").member("checksum")
+			},
+			Property{
+				key: Key::Computed(Expr::ident("VERSION_HEADER")),
+				value: Expr::ident("SCHEMA_VERSION")
+			}
+		]);
+		let validate_requests = options.validate_requests;
+		let mut fetch_options = vec![("headers",headers)];
+		if http_method != HttpMethod::Get{
+			fetch_options.push(("method",Expr::string(http_method.as_str())));
+		}
+		if let Some(input) = endpoint.input.as_ref().filter(|_|input_type.is_some()){
+			let payload = Expr::ident("payload");
+			let mut payload = convert(Direction::Encode, input, payload.clone()).unwrap_or(payload);
+			if validate_requests{
+				payload = check(input, payload);
+			}
+			fetch_options.push(("body",Expr::ident("JSON").member("stringify").call(vec![payload])));
+		}
+		body.push(Stmt::Const{
+			name: String::from("response"),
+			_type: None,
+			value: Expr::ident("fetch")
+				.call(vec![Expr::ident("url"),Expr::object(fetch_options)])
+				.await_()
+		});
+
+		// Endpoints with no return type respond with an empty body
+		let json = response.clone().member("json").call(vec![]).await_();
+		let returns = endpoint.returns.as_ref().filter(|_|http_method.has_response_body());
+		let success = match returns {
+			None => vec![Stmt::Return(None)],
+			Some(returns) => {
+				let body = check(returns, json.clone());
+				match convert(Direction::Decode, returns, Expr::ident("body")) {
+					Some(decoded) => vec![
+						Stmt::Const{name: String::from("body"), _type: None, value: body},
+						Stmt::Return(Some(decoded))
+					],
+					None => vec![Stmt::Return(Some(body))]
 				}
-			} catch (err){
-				throw err;
 			}
 		};
-		
-		
-		
-		MethodBuilder::new(name)
-			.add_param("payload", input_type)
+		body.push(Stmt::If{
+			condition: response.clone().member("ok"),
+			then: success
+		});
+
+		// HEAD responses never have a body, even for errors
+		if http_method.has_response_body(){
+			let error = Expr::ident("error");
+			body.push(Stmt::Const{
+				name: String::from("error"),
+				_type: None,
+				value: json
+			});
+			body.push(Stmt::If{
+				condition: Expr::ident("isRejection").call(vec![error.clone()]),
+				then: vec![Stmt::Throw(Expr::new_("RejectionError", vec![], vec![error.clone()]))]
+			});
+			body.extend(Self::declared_errors(name, endpoint));
+			body.push(Stmt::Throw(error));
+		} else {
+			body.push(Stmt::Throw(Expr::new_("Error", vec![], vec![response.member("statusText")])));
+		}
+
+		let mut builder = MethodBuilder::new(&Convention::Typescript.member(name));
+		for param in endpoint.path_params(){
+			let _type = endpoint.params.get(param)
+				.map(TsType::from)
+				.unwrap_or(TsType::String);
+			builder = builder.add_param(&Convention::Typescript.member(param), _type);
+		}
+
+		if has_query{
+			builder = builder.add_param("query", TsType::Custom(query_type_name(name)));
+		}
+
+		if let Some(input_type) = input_type{
+			builder = builder.add_param("payload", input_type);
+		}
+
+		builder
 			.returns(return_type)
 			.is_async()
-			.body(&method_body.to_string())
+			.body(body)
 			.build()	
 	}
-}
 
-impl MethodBuilder{
-	
-	fn gen_get_method(endpoint: &Endpoint) -> TokenStream{
-		let return_type:TsType = endpoint.returns.clone().into();
-		let uri = endpoint.uri.clone();
-		
-		quote::quote!{
-			try{
-				const response = await fetch(#uri,{
-					headers:{
-						"Api-Checksum": this.checksum
-					}
-				});
-				if (response.ok){
-					const body: #return_type = await response.json();
-					return body;
-				}
-				else{
-					const error = await response.json();
-					throw error;
+	/// Throw the endpoint's declared errors as an `ApiError`, with the 
+	/// body decoded.
+	fn declared_errors(name: &str, endpoint: &Endpoint) -> Vec<Stmt>{
+		let error_type = TsType::Custom(error_type_name(name));
+		endpoint.error_responses().into_iter().map(|error|{
+			let status = Expr::number(error.status);
+			let body = check(error._type, Expr::ident("error"));
+			let body = convert(Direction::Decode, error._type, body.clone()).unwrap_or(body);
+			let thrown = Expr::new_("ApiError", vec![error_type.clone()], vec![
+				Expr::object([("status",status.clone()),("body",body)])
+			]);
+			Stmt::If{
+				condition: Expr::ident("response").member("status").binary("===", status),
+				then: vec![Stmt::Throw(thrown)]
+			}
+		}).collect()
+	}
+
+	/// The parts of the url template, the path is appended to the client's
+	/// `baseUrl` and path parameters are encoded with `encodeURIComponent`.
+	/// Without a base url in the schema the client's `baseUrl` is optional
+	/// and defaults to the host in the endpoint's uri.
+	fn url_parts(endpoint: &Endpoint, with_base_url: bool) -> Vec<TemplatePart>{
+		let path = endpoint.path();
+		let mut base_url = Expr::ident("this").member("baseUrl");
+		if !with_base_url{
+			base_url = base_url.binary("??", Expr::string(endpoint.origin().unwrap_or("")));
+		}
+		let mut parts = vec![TemplatePart::Expr(base_url)];
+
+		let Ok(segments) = UriSegment::parse(path) else {
+			parts.push(TemplatePart::Text(String::from(path)));
+			return parts;
+		};
+
+		for segment in segments{
+			match segment {
+				UriSegment::Literal(text) => parts.push(TemplatePart::Text(String::from(text))),
+				UriSegment::Param(name) => {
+					let value = Expr::ident("String").call(vec![Expr::ident(&Convention::Typescript.member(name))]);
+					parts.push(TemplatePart::Expr(Expr::ident("encodeURIComponent").call(vec![value])));
 				}
-			} catch (err){
-				throw err;
 			}
 		}
+		parts
 	}
 
-	fn gen_post_method(endpoint: &Endpoint) -> TokenStream{
-		let input_type:TsType = endpoint.input.clone().into();
-		let return_type:TsType = endpoint.returns.clone().into();
-		let uri = endpoint.uri.clone();
-		
-		quote::quote!{
-			try{
-				const response = await fetch(#uri,{
-					headers:{
-						"Api-Checksum": this.checksum
-					},
-					method: "POST"
-				});
-				if (response.ok){
-					const body: #return_type = await response.json();
-					return body;
-				}
-				else{
-					const error = await response.json();
-					throw error;
+	/// Serialize the `query` parameter into a `search` string, missing 
+	/// values are skipped and every item of a list is appended with the 
+	/// same key.
+	fn query_string(endpoint: &Endpoint) -> Vec<Stmt>{
+		let params = Expr::ident("params");
+		let mut statements = vec![Stmt::Const{
+			name: String::from("params"),
+			_type: None,
+			value: Expr::new_("URLSearchParams", vec![], vec![])
+		}];
+
+		for (key,value) in &endpoint.query{
+			let field = Expr::ident("query").member(&Convention::Typescript.member(key));
+			let append = |value: Expr|{
+				let value = Expr::ident("String").call(vec![value]);
+				params.clone().member("append").call(vec![Expr::string(key),value])
+			};
+			let statement = match value {
+				SchemaType::Array(inner) => {
+					let item = Param::new("item", inner.as_ref().into());
+					let callback = Expr::arrow(vec![item], append(Expr::ident("item")));
+					Stmt::Expr(field.member("forEach").call(vec![callback]))
+				},
+				_ => Stmt::If{
+					condition: field.clone().binary("!=", Expr::ident("null")),
+					then: vec![Stmt::Expr(append(field))]
 				}
-			} catch (err){
-				throw err;
-			}
+			};
+			statements.push(statement);
 		}
+
+		statements.push(Stmt::Const{
+			name: String::from("search"),
+			_type: None,
+			value: params.member("toString").call(vec![])
+		});
+		statements
 	}
-	
 }
 
 #[cfg(test)]
@@ -280,10 +431,10 @@ mod tests{
 			.is_async()
 			.add_param("uid", TsType::Number)
 			.returns(TsType::Custom("User".to_string()))
-			.body("")
+			.body(vec![Stmt::Return(Some(Expr::ident("user")))])
 			.build();
 
-		let body = concat!("async get_user(uid: number,): Promise<User> {","\n","\n}");
+		let body = concat!("async get_user(uid: number): Promise<User> {\n","\treturn user;\n","}");
 		
 		assert_eq!(format!("{}",method),body);
 	}
@@ -291,10 +442,9 @@ mod tests{
 	#[test]
 	fn sync_method(){
 		let method = MethodBuilder::new("init")
-			.body("")
 			.build();
 
-		let body = concat!("init() {","\n}");
+		let body = "init() {}";
 		
 		assert_eq!(format!("{}",method),body);
 	}
@@ -13,30 +13,46 @@
 //! output:
 //! 
 //! ```typescript
-//! interface User{
-//! 	id: number,
-//! 	name: string,
-//! 	email: string,
-//! 	created_at: string
+//! export interface User {
+//! 	id: number;
+//! 	name: string;
+//! 	email: string;
+//! 	createdAt: string;
 //! }
 //! ```
-use std::{collections::HashMap, fs};
-use core_types::{ApiSchema, SchemaType};
+//! 
+//! Fields, parameters and methods are camel case and types are pascal 
+//! case. The client converts values to and from the wire format, which 
+//! keeps the schema's names, with the generated `decodeUser` and 
+//! `encodeUser` functions.
+//! 
+//! Function bodies are built from [`Stmt`] and [`Expr`] and everything is
+//! printed the way prettier would format it, so the output can be checked
+//! in with the rest of a project.
+use std::{fs, path::{Path, PathBuf}};
+use core_types::{naming::{error_type_name, pascal_case, query_type_name, Convention}, validate, ApiSchema, Endpoint, IndexMap, SchemaType};
+mod ast;
 mod class;
+mod convert;
 mod method;
+mod printer;
+mod union;
+mod validator;
+pub use ast::*;
 pub use method::*;
 pub use class::*;
-use proc_macro2::Span;
-use quote::{quote, ToTokens, TokenStreamExt};
+pub use union::*;
+use printer::{block, concat, print, text, Doc};
+use quote::ToTokens;
 
 /// Represents a typescript interface
 /// 
 /// ```typescript
-/// interface User{
-/// 	id: string,
-/// 	email: string,
-/// 	name?: string,
-/// 	createdAt: string,
+/// export interface User {
+/// 	id: string;
+/// 	email: string;
+/// 	name?: string;
+/// 	createdAt: string;
 /// }
 /// ```
 #[derive(Debug)]
@@ -47,14 +63,9 @@ struct Interface{
 
 impl Interface {
 	fn new(name:&str) -> Self{
-		// TODO make sure it's a valid identifier
 		Self { name: String::from(name), fields: vec![] }
 	}
 
-	fn push_field(&mut self,field: Field){
-		self.fields.push(field);
-	}
-
 	fn push_fields<I>(&mut self,fields: I)
 	where I: IntoIterator<Item = Field>
 	{
@@ -63,17 +74,11 @@ impl Interface {
 		}
 	}
 
-	/// Generate a typescript interface as a string
-	fn gen_code(&self) -> String{
-		let mut contents = String::new();
-		contents.push_str(&format!("export interface {}{{\n",self.name));
-
-		for field in &self.fields{
-			contents.push_str(&format!("\t{},\n",field));
-		}
-		contents.push_str("}\n\n");
-
-		contents
+	fn to_doc(&self) -> Doc{
+		let fields = self.fields.iter()
+			.map(|field|concat([field.to_doc(),text(";")]))
+			.collect();
+		concat([text(format!("export interface {} ",self.name)),block(fields)])
 	}
 }
 
@@ -85,14 +90,28 @@ pub struct Field{
 }
 
 impl Field {
-	fn new(name:&str, _type: TsType) -> Self{
+	pub fn new(name:&str, _type: TsType) -> Self{
 		Self { name: String::from(name), _type }
 	}
+
+	/// Unions that don't fit on the line get one member per line.
+	fn to_doc(&self) -> Doc{
+		match &self._type {
+			TsType::Union(members) => concat([
+				text(format!("{}:",self.name)),
+				ast::union_members(members.iter().map(|member|text(member.to_string())).collect())
+			]),
+			_ => text(self.to_string())
+		}
+	}
 }
 
 impl std::fmt::Display for Field{
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-		f.write_str(&format!("{}: {}",self.name,self._type))
+		match &self._type {
+			TsType::Optional(_type) => write!(f,"{}?: {}",self.name,_type),
+			_type => write!(f,"{}: {}",self.name,_type)
+		}
 	}
 }
 
@@ -105,8 +124,24 @@ pub enum TsType{
 	String,
 	/// `boolean`
 	Boolean,
+	/// `any`, the type of values read from the wire
+	Any,
 	/// `T[]`
 	Array(Box<TsType>),
+	/// `T | null`, fields with this type are written as `name?: T`
+	Optional(Box<TsType>),
+	/// `Record<string, T>`
+	Record(Box<TsType>),
+	/// `[A, B]`
+	Tuple(Vec<TsType>),
+	/// A literal type written as is, `"active"` or `404`
+	Literal(String),
+	/// `A | B`
+	Union(Vec<TsType>),
+	/// An object type, `{ status: 404; body: NotFound }`
+	Object(Vec<Field>),
+	/// `void`
+	Void,
 	Custom(String),
 }
 
@@ -116,32 +151,51 @@ impl std::fmt::Display for TsType{
 			Self::Number => f.write_str("number"), 
 			Self::String => f.write_str("string"), 
 			Self::Boolean => f.write_str("boolean"), 
-			Self::Array(_type) => f.write_str(&format!("{}[]",_type)), 
-			Self::Custom(_type) => f.write_str(&_type.to_string()), 
+			Self::Any => f.write_str("any"), 
+			Self::Array(_type) => match _type.as_ref() {
+				TsType::Optional(_)|
+				TsType::Union(_) => write!(f,"({})[]",_type),
+				_ => write!(f,"{}[]",_type)
+			}, 
+			Self::Optional(_type) => write!(f,"{} | null",_type), 
+			Self::Record(_type) => write!(f,"Record<string, {}>",_type), 
+			Self::Tuple(types) => {
+				let types: Vec<String> = types.iter().map(TsType::to_string).collect();
+				write!(f,"[{}]",types.join(", "))
+			},
+			Self::Literal(value) => f.write_str(value), 
+			Self::Union(types) => {
+				let types: Vec<String> = types.iter().map(TsType::to_string).collect();
+				f.write_str(&types.join(" | "))
+			},
+			Self::Object(fields) if fields.is_empty() => f.write_str("{}"),
+			Self::Object(fields) => {
+				let fields: Vec<String> = fields.iter().map(Field::to_string).collect();
+				write!(f,"{{ {} }}",fields.join("; "))
+			},
+			Self::Void => f.write_str("void"), 
+			Self::Custom(_type) => f.write_str(_type), 
 		}
 	}
 }
 
 impl From<SchemaType> for TsType {
 	fn from(value: SchemaType) -> Self {
-		match value {
-			SchemaType::Int | 
-			SchemaType::Float => Self::Number,
-			SchemaType::String => Self::String,
-			SchemaType::Boolean => Self::Boolean,
-			SchemaType::Struct(ident) => Self::Custom(ident)
-		}
+		Self::from(&value)
 	}
 }
 
 impl From<&SchemaType> for TsType {
 	fn from(value: &SchemaType) -> Self {
 		match value {
-			&SchemaType::Int|
-			&SchemaType::Float => Self::Number,
-			&SchemaType::String => Self::String,
-			&SchemaType::Boolean => Self::Boolean,
-			SchemaType::Struct(ident) => Self::Custom(ident.clone())
+			SchemaType::Int|
+			SchemaType::Float => Self::Number,
+			SchemaType::String => Self::String,
+			SchemaType::Boolean => Self::Boolean,
+			SchemaType::Array(_type) => Self::Array(Box::new(_type.as_ref().into())),
+			SchemaType::Optional(_type) => Self::Optional(Box::new(_type.as_ref().into())),
+			SchemaType::Map(_type) => Self::Record(Box::new(_type.as_ref().into())),
+			SchemaType::Struct(ident) => Self::Custom(pascal_case(ident))
 		}
 	}
 }
@@ -149,87 +203,311 @@ impl From<&SchemaType> for TsType {
 
 impl ToTokens for TsType{
 	fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-		match self {
-			Self::Number => {
-				let ident = proc_macro2::Ident::new("number", Span::call_site());
-				tokens.append(ident);
-			}
-			Self::String => {
-				let ident = proc_macro2::Ident::new("string", Span::call_site());
-				tokens.append(ident);
-			}
-			Self::Array(name) => {
-				let ident = proc_macro2::Ident::new(&format!("{}[]",name), Span::call_site());
-				tokens.append(ident);
-			}
-			Self::Boolean => {
-				let ident = proc_macro2::Ident::new("boolean", Span::call_site());
-				tokens.append(ident);
-			}
-			Self::Custom(name) => {
-				let ident = proc_macro2::Ident::new(name, Span::call_site());
-				tokens.append(ident);
-			}
-		}
+		// Compound types such as `string[]` and `Record<string, number>` are not
+		// single identifiers, so go through the display representation
+		let stream: proc_macro2::TokenStream = self.to_string()
+			.parse()
+			.expect("Typescript types should be valid tokens");
+		tokens.extend(stream);
 	}
 }
 
-// TODO check if the custom structs are in the schema
+/// Generate the typescript code for the schema and write it to `file_path`.
 pub fn codegen(config_path:&str,file_path:&str) -> crate::Result<()>{
-	let contents = fs::read_to_string(config_path)?;
-	let schema = ApiSchema::parse(&contents)?;
+	let (schema,files) = ApiSchema::load(config_path)?;
+	validate::validate_files(&schema, &files)?;
+
+	fs::write(file_path, render(&schema))?;
+	Ok(())
+}
 
-	let mut interfaces = vec![];
+/// Generate the typescript code for the schema and write it to the 
+/// `codegen.typescript.output` file, relative to the schema file. Returns
+/// the path that was written to.
+pub fn generate(config_path: impl AsRef<Path>) -> crate::Result<PathBuf>{
+	let config_path = config_path.as_ref();
+	let (schema,files) = ApiSchema::load(config_path)?;
+	validate::validate_files(&schema, &files)?;
+
+	let Some(output) = &schema.codegen.typescript.output else {
+		return Err(crate::Error::MissingOutput(String::from("typescript")));
+	};
+	let output = config_path.parent()
+		.unwrap_or(Path::new(""))
+		.join(output);
+	fs::write(&output, render(&schema))?;
+	Ok(output)
+}
+
+/// Generate the typescript code for a validated schema.
+fn render(schema: &ApiSchema) -> String{
+	// Values read from the wire are untyped until they're decoded
+	let mut items = vec![text("/* eslint-disable @typescript-eslint/no-explicit-any */")];
 
 	for (key,value) in schema.structs.iter(){
-		let mut interface = Interface::new(key);
-		let fields = parse_interface_fields(value);
-		interface.push_fields(fields);
-		interfaces.push(interface);
+		let mut interface = Interface::new(&pascal_case(key));
+		interface.push_fields(parse_interface_fields(value));
+		items.push(interface.to_doc());
 	}
-	let mut contents = String::new();
-	
-	for interface in interfaces{
-		contents.push_str(&interface.gen_code());
+
+	for (name,endpoint) in schema.endpoints.iter(){
+		if endpoint.query.is_empty(){
+			continue;
+		}
+		let mut interface = Interface::new(&query_type_name(name));
+		interface.push_fields(parse_interface_fields(&endpoint.query));
+		items.push(interface.to_doc());
+	}
+
+	for (key,value) in schema.enums.iter(){
+		items.push(Union::from_enum(&pascal_case(key), value).to_doc());
+	}
+
+	for (key,value) in schema.structs.iter(){
+		items.extend(convert::struct_converters(key, value).iter().map(Function::to_doc));
+	}
+
+	for (key,value) in schema.enums.iter(){
+		items.extend(convert::enum_converters(key, value).iter().map(Function::to_doc));
+	}
+
+	items.push(text(validator::runtime()));
+	for (key,value) in schema.structs.iter(){
+		items.push(validator::struct_validator(key, value).to_doc());
+	}
+
+	for (key,value) in schema.enums.iter(){
+		items.push(validator::enum_validator(key, value).to_doc());
 	}
 
-	// Create client
-	let mut client = Class::new("Client");
+	for (name,endpoint) in schema.endpoints.iter(){
+		items.extend(error_union(name, endpoint));
+	}
+
+	items.push(constant(
+		"The checksum of the schema, sent with every request.",
+		"SCHEMA_CHECKSUM",
+		&schema.checksum()
+	));
+	items.push(constant("The header that the checksum is sent in.", "CHECKSUM_HEADER", schema.checksum_header()));
+	items.push(constant(
+		"The version of the schema, sent with every request.",
+		"SCHEMA_VERSION",
+		&schema.version.to_string()
+	));
+	items.push(constant("The header that the version is sent in.", "VERSION_HEADER", schema.version_header()));
+
+	if let Some(base_url) = &schema.codegen.base_url{
+		items.push(constant(
+			"The url that requests are sent to by default.",
+			"BASE_URL",
+			base_url.trim_end_matches('/')
+		));
+	}
+
+	items.extend(rejection_types());
+	if schema.endpoints.values().any(|endpoint|!endpoint.errors.is_empty()){
+		items.push(api_error_class());
+	}
+
+	items.push(client_options());
+
+	// Create client, without a base url in the schema requests go to the
+	// host in each endpoint's uri unless the `baseUrl` is set
+	let has_base_url = schema.codegen.base_url.is_some();
+	let headers = TsType::Record(Box::new(TsType::String));
+	let mut client = Class::new(schema.codegen.typescript_client_name());
 	client.push_field(Field::new("checksum", TsType::String));
+	let this = Expr::ident("this");
+	let options = Expr::ident("options");
+	let mut base_url = options.clone().optional_member("baseUrl");
+	if has_base_url{
+		client.push_field(Field::new("baseUrl", TsType::String));
+		base_url = base_url.binary("??", Expr::ident("BASE_URL"));
+	} else {
+		client.push_field(Field::new("baseUrl", TsType::Optional(Box::new(TsType::String))));
+	}
+	client.push_field(Field::new("headers", headers));
+	let constructor = vec![
+		Stmt::Expr(this.clone().member("checksum").assign(Expr::ident("SCHEMA_CHECKSUM"))),
+		Stmt::Expr(this.clone().member("baseUrl").assign(base_url)),
+		Stmt::Expr(this.member("headers").assign(
+			options.optional_member("headers").binary("??", Expr::Object(vec![]))
+		))
+	];
 
 	let constructor = MethodBuilder::new("constructor")
-		.add_param("checksum", TsType::String)
-		.body(
-			&quote! {
-				this.checksum = checksum;
-			}.to_string()
-		)
+		.add_param("options", TsType::Optional(Box::new(TsType::Custom(String::from("ClientOptions")))))
+		.body(constructor)
 		.build();
 	
 	client.push_method(constructor);
 
 	// Create route endpoint functions 
-	for (name,endpoint) in schema.endpoints{
-		let method = MethodBuilder::from_endpoint(&name, &endpoint);
-		
-		client.push_method(method);
+	let options = EndpointOptions{
+		base_url: has_base_url,
+		validate_requests: schema.codegen.typescript.validate_requests
+	};
+	for (name,endpoint) in &schema.endpoints{
+		client.push_method(MethodBuilder::from_endpoint_with_options(name, endpoint, options));
 	}
 
+	items.push(client.to_doc());
 
-	contents.push_str(&format!("{}",client));
-	
-	fs::write(file_path, contents)?;
+	let items: Vec<String> = items.iter().map(print).collect();
+	format!("{}\n",items.join("\n\n"))
+}
 
-	Ok(())
+/// An exported string constant with a doc comment.
+fn constant(doc: &str, name: &str, value: &str) -> Doc{
+	concat([
+		text(format!("/** {doc} */")),
+		Doc::HardLine,
+		text(format!("export const {name} = {value:?};"))
+	])
+}
+
+/// The options the client is created with.
+/// 
+/// ```typescript
+/// const client = new Client({
+/// 	baseUrl: "http://localhost:3000",
+/// 	headers: { Authorization: `Bearer ${token}` },
+/// });
+/// ```
+fn client_options() -> Doc{
+	let optional = |_type: TsType|TsType::Optional(Box::new(_type));
+	let mut interface = Interface::new("ClientOptions");
+	interface.push_fields([
+		Field::new("baseUrl", optional(TsType::String)),
+		Field::new("headers", optional(TsType::Record(Box::new(TsType::String))))
+	]);
+
+	concat([
+		text("/** Overrides the url that requests are sent to, and headers sent with every request. */"),
+		Doc::HardLine,
+		interface.to_doc()
+	])
+}
+
+/// The [`Rejection`](core_types::Rejection) body sent by the server, the
+/// error thrown when a request is rejected and a type guard for it.
+fn rejection_types() -> Vec<Doc>{
+	let codes: Vec<String> = core_types::RejectionCode::all()
+		.iter()
+		.map(|code|code.to_string())
+		.collect();
+
+	let optional = |name: &str, _type: TsType|Field::new(name, TsType::Optional(Box::new(_type)));
+	let mut interface = Interface::new("Rejection");
+	interface.push_fields([
+		Field::new("code", TsType::Union(codes.iter().map(|code|TsType::Literal(format!("{:?}",code))).collect())),
+		Field::new("message", TsType::String),
+		optional("expected_checksum", TsType::String),
+		optional("received_checksum", TsType::String),
+		optional("server_version", TsType::String),
+		optional("client_version", TsType::String),
+		optional("errors", TsType::Array(Box::new(TsType::Object(vec![
+			Field::new("path", TsType::String),
+			Field::new("message", TsType::String)
+		])))),
+	]);
+
+	let is_rejection = Function{
+		exported: false,
+		name: String::from("isRejection"),
+		params: vec![Field::new("value", TsType::Any)],
+		returns: TsType::Custom(String::from("value is Rejection")),
+		body: vec![Stmt::Return(Some(
+			Expr::ident("value")
+				.binary("!=", Expr::ident("null"))
+				.binary("&&", Expr::ident("REJECTION_CODES").member("includes").call(vec![
+					Expr::ident("value").member("code")
+				]))
+		))]
+	};
+
+	vec![
+		concat([
+			text("/** The body sent when the server rejects a request, or the handler's response doesn't match the schema. */"),
+			Doc::HardLine,
+			interface.to_doc()
+		]),
+		text(indoc::indoc!{r#"
+			/**
+			 * Thrown when the server rejects a request, the `code` says why. An
+			 * `invalid_response` code is a bug in the server, not in the client.
+			 */
+			export class RejectionError extends Error {
+				rejection: Rejection;
+
+				constructor(rejection: Rejection) {
+					super(rejection.message);
+					this.rejection = rejection;
+				}
+			}"#
+		}),
+		Stmt::Const{
+			name: String::from("REJECTION_CODES"),
+			_type: None,
+			value: Expr::Array(codes.iter().map(|code|Expr::string(code)).collect())
+		}.to_doc(),
+		is_rejection.to_doc()
+	]
+}
+
+/// A union of an endpoint's declared errors, discriminated by the status 
+/// code, endpoints without errors have no union.
+/// 
+/// ```typescript
+/// export type GetUserError =
+/// 	| { status: 404; body: NotFound }
+/// 	| { status: 409; body: Conflict };
+/// ```
+fn error_union(name: &str, endpoint: &Endpoint) -> Option<Doc>{
+	let errors = endpoint.error_responses();
+	if errors.is_empty(){
+		return None;
+	}
+
+	let members = errors.into_iter()
+		.map(|error|TsType::Object(vec![
+			Field::new("status", TsType::Literal(error.status.to_string())),
+			Field::new("body", TsType::from(error._type))
+		]))
+		.collect();
+	Some(concat([
+		text(format!("/** The errors declared by `{}`. */",name)),
+		Doc::HardLine,
+		union::type_alias(&error_type_name(name), members)
+	]))
+}
+
+/// The error thrown when the server responds with one of the endpoint's 
+/// declared errors.
+fn api_error_class() -> Doc{
+	text(indoc::indoc!{r#"
+		/** Thrown when the server responds with one of the endpoint's declared errors. */
+		export class ApiError<
+			E extends { status: number; body: unknown },
+		> extends Error {
+			error: E;
+
+			constructor(error: E) {
+				super(`the server responded with ${error.status}`);
+				this.error = error;
+			}
+		}"#
+	})
 }
 
-/// Parse typescript interface fields
-fn parse_interface_fields(values: &HashMap<String,SchemaType>) -> Vec<Field>{
+/// Parse typescript interface fields, the names are converted to camel case
+fn parse_interface_fields(values: &IndexMap<String,SchemaType>) -> Vec<Field>{
 	let mut fields = vec![];
 
 	for (key,value) in values.iter(){
 		let _type = TsType::from(value);
-		let field = Field::new(key, _type);
+		let field = Field::new(&Convention::Typescript.member(key), _type);
 		fields.push(field);
 	}
 
@@ -245,8 +523,42 @@ mod tests{
 		assert_eq!(format!("{}",TsType::Number),"number");
 		assert_eq!(format!("{}",TsType::String),"string");
 		assert_eq!(format!("{}",TsType::Boolean),"boolean");
+		assert_eq!(format!("{}",TsType::Void),"void");
 
 		let number_array = TsType::Array(Box::new(TsType::Number));
 		assert_eq!(format!("{}",number_array),"number[]");
+
+		let optional = TsType::Optional(Box::new(TsType::String));
+		assert_eq!(format!("{}",optional),"string | null");
+		
+		let optional_array = TsType::Array(Box::new(optional));
+		assert_eq!(format!("{}",optional_array),"(string | null)[]");
+
+		let record = TsType::Record(Box::new(TsType::Number));
+		assert_eq!(format!("{}",record),"Record<string, number>");
+	}
+
+	#[test]
+	fn schema_type_conversion(){
+		let tags: SchemaType = "string[]".parse().unwrap();
+		assert_eq!(TsType::from(&tags),TsType::Array(Box::new(TsType::String)));
+		
+		let metadata: SchemaType = "map<string, int>".parse().unwrap();
+		assert_eq!(TsType::from(&metadata),TsType::Record(Box::new(TsType::Number)));
+		
+		let manager: SchemaType = "User?".parse().unwrap();
+		assert_eq!(
+			TsType::from(&manager),
+			TsType::Optional(Box::new(TsType::Custom("User".to_string())))
+		);
+	}
+
+	#[test]
+	fn optional_field_display(){
+		let field = Field::new("nickname", TsType::Optional(Box::new(TsType::String)));
+		assert_eq!(format!("{}",field),"nickname?: string");
+		
+		let field = Field::new("tags", TsType::Array(Box::new(TsType::String)));
+		assert_eq!(format!("{}",field),"tags: string[]");
 	}
 }
\ No newline at end of file
@@ -0,0 +1,236 @@
+//! A small pretty printer for the generated typescript.
+//!
+//! The code is first turned into a [`Doc`], which marks the places where
+//! lines can be broken, and then printed breaking the groups that don't
+//! fit on a line, the same way prettier does. The output is indented with
+//! tabs.
+
+/// The maximum width of a line.
+pub const WIDTH: usize = 80;
+/// The width a tab counts as when measuring a line.
+const TAB_WIDTH: usize = 2;
+
+/// A document to print.
+#[derive(Debug,Clone,PartialEq)]
+pub enum Doc{
+	Text(String),
+	/// A space, or a new line when the enclosing group is broken
+	Line,
+	/// Nothing, or a new line when the enclosing group is broken
+	SoftLine,
+	/// Always a new line, the groups around it are always broken
+	HardLine,
+	/// Indent the lines in the document one level deeper
+	Indent(Box<Doc>),
+	Concat(Vec<Doc>),
+	/// A document that is printed on one line if it fits
+	Group(Box<Doc>),
+	/// The first document when the enclosing group is broken, the second
+	/// otherwise
+	IfBreak(Box<Doc>,Box<Doc>)
+}
+
+pub fn text(text: impl Into<String>) -> Doc{
+	Doc::Text(text.into())
+}
+
+pub fn concat(docs: impl IntoIterator<Item = Doc>) -> Doc{
+	Doc::Concat(docs.into_iter().collect())
+}
+
+pub fn group(doc: Doc) -> Doc{
+	Doc::Group(Box::new(doc))
+}
+
+pub fn indent(doc: Doc) -> Doc{
+	Doc::Indent(Box::new(doc))
+}
+
+pub fn if_break(broken: Doc, flat: Doc) -> Doc{
+	Doc::IfBreak(Box::new(broken), Box::new(flat))
+}
+
+/// Put the `separator` between each of the documents.
+pub fn join(docs: impl IntoIterator<Item = Doc>, separator: Doc) -> Doc{
+	let mut joined = vec![];
+	for (i,doc) in docs.into_iter().enumerate(){
+		if i > 0{
+			joined.push(separator.clone());
+		}
+		joined.push(doc);
+	}
+	Doc::Concat(joined)
+}
+
+/// A list in brackets, e.g. call arguments, that is put on one line if it
+/// fits, otherwise each item gets its own line and a trailing comma.
+pub fn list(open: &str, items: Vec<Doc>, close: &str) -> Doc{
+	if items.is_empty(){
+		return text(format!("{open}{close}"));
+	}
+
+	group(concat([
+		text(open),
+		indent(concat([
+			Doc::SoftLine,
+			join(items, concat([text(","),Doc::Line])),
+			if_break(text(","), text(""))
+		])),
+		Doc::SoftLine,
+		text(close)
+	]))
+}
+
+/// A block of lines in braces that is always broken, e.g. a function body.
+pub fn block(lines: Vec<Doc>) -> Doc{
+	if lines.is_empty(){
+		return text("{}");
+	}
+
+	concat([
+		text("{"),
+		indent(concat(lines.into_iter().map(|line|concat([Doc::HardLine,line])))),
+		Doc::HardLine,
+		text("}")
+	])
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+enum Mode{
+	Flat,
+	Break
+}
+
+/// Print the document, breaking the groups that don't fit in [`WIDTH`].
+pub fn print(doc: &Doc) -> String{
+	print_in(doc, Mode::Break)
+}
+
+/// Print the document without breaking any groups, e.g. the expressions
+/// in a template literal.
+pub fn print_flat(doc: &Doc) -> String{
+	print_in(doc, Mode::Flat)
+}
+
+fn print_in(doc: &Doc, mode: Mode) -> String{
+	let mut out = String::new();
+	let mut column = 0;
+	// Indentation is written lazily so that blank lines stay empty
+	let mut pending_indent: Option<usize> = None;
+	let mut stack: Vec<(usize,Mode,&Doc)> = vec![(0,mode,doc)];
+
+	while let Some((level,mode,doc)) = stack.pop(){
+		match doc {
+			Doc::Text(text) => {
+				if text.is_empty(){
+					continue;
+				}
+				if let Some(level) = pending_indent.take(){
+					out.extend(std::iter::repeat_n('\t', level));
+				}
+				out.push_str(text);
+				column += text.chars().count();
+			},
+			Doc::Line|Doc::SoftLine if mode == Mode::Flat => {
+				if *doc == Doc::Line{
+					out.push(' ');
+					column += 1;
+				}
+			},
+			Doc::Line|Doc::SoftLine|Doc::HardLine => {
+				out.push('\n');
+				pending_indent = Some(level);
+				column = level * TAB_WIDTH;
+			},
+			Doc::Indent(doc) => stack.push((level + 1,mode,doc)),
+			Doc::Concat(docs) => {
+				for doc in docs.iter().rev(){
+					stack.push((level,mode,doc));
+				}
+			},
+			Doc::Group(doc) => {
+				let mode = match mode {
+					Mode::Flat => Mode::Flat,
+					Mode::Break if fits(doc, &stack, WIDTH.saturating_sub(column)) => Mode::Flat,
+					Mode::Break => Mode::Break
+				};
+				stack.push((level,mode,doc));
+			},
+			Doc::IfBreak(broken,flat) => match mode {
+				Mode::Break => stack.push((level,mode,broken)),
+				Mode::Flat => stack.push((level,mode,flat))
+			}
+		}
+	}
+
+	out
+}
+
+/// Check that the document fits in the `width` when printed flat, along
+/// with whatever follows it up to the next line break.
+fn fits(doc: &Doc, rest: &[(usize,Mode,&Doc)], width: usize) -> bool{
+	let mut remaining = width as isize;
+	let mut stack: Vec<(Mode,&Doc)> = vec![(Mode::Flat,doc)];
+	let mut rest = rest.iter().rev();
+
+	loop {
+		let Some((mode,doc)) = stack.pop().or_else(||rest.next().map(|(_,mode,doc)|(*mode,*doc))) else {
+			return true;
+		};
+
+		match doc {
+			Doc::Text(text) => remaining -= text.chars().count() as isize,
+			Doc::Line if mode == Mode::Flat => remaining -= 1,
+			Doc::SoftLine if mode == Mode::Flat => {},
+			Doc::HardLine if mode == Mode::Flat => return false,
+			Doc::Line|Doc::SoftLine|Doc::HardLine => return true,
+			Doc::Indent(doc) => stack.push((mode,doc)),
+			Doc::Concat(docs) => {
+				for doc in docs.iter().rev(){
+					stack.push((mode,doc));
+				}
+			},
+			Doc::Group(doc) => stack.push((mode,doc)),
+			Doc::IfBreak(broken,flat) => match mode {
+				Mode::Break => stack.push((mode,broken)),
+				Mode::Flat => stack.push((mode,flat))
+			}
+		}
+
+		if remaining < 0{
+			return false;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	fn call(name: &str, args: &[&str]) -> Doc{
+		concat([text(name),list("(", args.iter().map(|arg|text(*arg)).collect(), ")")])
+	}
+
+	#[test]
+	fn flat_group(){
+		assert_eq!(print(&call("fetch", &["url","options"])),"fetch(url, options)");
+		assert_eq!(print(&call("init", &[])),"init()");
+	}
+
+	#[test]
+	fn broken_group(){
+		let argument = "a".repeat(40);
+		let doc = concat([call("fetch", &[&argument,&argument]),text(";")]);
+		assert_eq!(print(&doc),format!("fetch(\n\t{argument},\n\t{argument},\n);"));
+	}
+
+	#[test]
+	fn blocks(){
+		let doc = concat([
+			text("function init() "),
+			block(vec![text("start();"),text(""),text("stop();")])
+		]);
+		assert_eq!(print(&doc),"function init() {\n\tstart();\n\n\tstop();\n}");
+		assert_eq!(print(&block(vec![])),"{}");
+	}
+}
@@ -0,0 +1,171 @@
+use core_types::{naming::Convention, Enum};
+use super::{ast::union_members, printer::{concat, print, text, Doc}, Field, TsType};
+
+/// A typescript union type alias, the members go on their own lines when
+/// they don't fit on one
+///
+/// ```typescript
+/// export type Status = "active" | "suspended";
+///
+/// export type Shape =
+/// 	| { kind: "Circle"; radiusInMillimeters: number }
+/// 	| { kind: "Square"; sideInMillimeters: number };
+/// ```
+#[derive(Debug,Clone,PartialEq, Eq, PartialOrd, Ord)]
+pub struct Union{
+	name: String,
+	variants: Vec<Variant>
+}
+
+/// A single member of a [`Union`]
+#[derive(Debug,Clone,PartialEq, Eq, PartialOrd, Ord)]
+pub enum Variant{
+	/// A string literal, `"active"`
+	Literal(String),
+	/// An object with a discriminating tag field, `{ kind: "Circle"; radius: number }`
+	Tagged{
+		tag: String,
+		name: String,
+		fields: Vec<Field>
+	}
+}
+
+impl Union{
+	pub fn new(name:&str) -> Self{
+		Self {
+			name: String::from(name),
+			variants: vec![]
+		}
+	}
+
+	/// Create a union from a schema [`Enum`], the values and variant names
+	/// are kept as they are on the wire while the fields are camel case
+	pub fn from_enum(name:&str,value: &Enum) -> Self{
+		let mut union = Self::new(name);
+
+		match value {
+			Enum::Simple(variants) => {
+				for variant in variants{
+					union.push_variant(Variant::Literal(variant.clone()));
+				}
+			},
+			Enum::Tagged(tagged) => {
+				for (variant,fields) in tagged.variants.iter(){
+					let fields = fields.iter()
+						.map(|(key,value)|Field::new(&Convention::Typescript.member(key), TsType::from(value)))
+						.collect();
+
+					union.push_variant(Variant::Tagged {
+						tag: Convention::Typescript.member(&tagged.tag),
+						name: variant.clone(),
+						fields
+					});
+				}
+			}
+		}
+
+		union
+	}
+
+	pub fn push_variant(&mut self, variant: Variant){
+		self.variants.push(variant);
+	}
+}
+
+impl Variant{
+	/// The type of the variant's values.
+	pub fn to_type(&self) -> TsType{
+		match self {
+			Self::Literal(value) => TsType::Literal(format!("{:?}",value)),
+			Self::Tagged { tag, name, fields } => {
+				let tag = Field::new(tag, TsType::Literal(format!("{:?}",name)));
+				TsType::Object(std::iter::once(tag).chain(fields.iter().cloned()).collect())
+			}
+		}
+	}
+}
+
+impl std::fmt::Display for Variant{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f,"{}",self.to_type())
+	}
+}
+
+impl Union{
+	pub(crate) fn to_doc(&self) -> Doc{
+		type_alias(&self.name, self.variants.iter().map(Variant::to_type).collect())
+	}
+}
+
+impl std::fmt::Display for Union{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&print(&self.to_doc()))
+	}
+}
+
+/// An exported alias for the union of the `members`.
+pub(crate) fn type_alias(name: &str, members: Vec<TsType>) -> Doc{
+	let declaration = format!("export type {} =",name);
+	match members.as_slice() {
+		[] => text(format!("{declaration} never;")),
+		[member] => text(format!("{declaration} {member};")),
+		_ => concat([
+			text(declaration),
+			union_members(members.iter().map(|member|text(member.to_string())).collect()),
+			text(";")
+		])
+	}
+}
+
+#[cfg(test)]
+mod tests{
+	use core_types::TaggedUnion;
+	use core_types::IndexMap;
+	use super::*;
+
+	#[test]
+	fn string_union_display(){
+		let status = Enum::Simple(vec!["active".to_string(),"suspended".to_string()]);
+		let union = Union::from_enum("Status", &status);
+
+		assert_eq!(
+			format!("{}",union),
+			r#"export type Status = "active" | "suspended";"#
+		);
+	}
+
+	#[test]
+	fn tagged_union_display(){
+		let shape = Enum::Tagged(TaggedUnion{
+			tag: "kind".to_string(),
+			variants: IndexMap::from([
+				("Circle".to_string(),IndexMap::from([("radius".to_string(),core_types::SchemaType::Float)])),
+			])
+		});
+		let union = Union::from_enum("Shape", &shape);
+
+		assert_eq!(
+			format!("{}",union),
+			r#"export type Shape = { kind: "Circle"; radius: number };"#
+		);
+	}
+
+	#[test]
+	fn long_union_display(){
+		let fields = |name: &str|IndexMap::from([(name.to_string(),core_types::SchemaType::Float)]);
+		let shape = Enum::Tagged(TaggedUnion{
+			tag: "kind".to_string(),
+			variants: IndexMap::from([
+				("Circle".to_string(),fields("radius_in_millimeters")),
+				("Square".to_string(),fields("side_in_millimeters")),
+			])
+		});
+		let union = Union::from_enum("Shape", &shape);
+
+		assert_eq!(format!("{}",union),concat!(
+			"export type Shape =\n",
+			"\t| { kind: \"Circle\"; radiusInMillimeters: number }\n",
+			"\t| { kind: \"Square\"; sideInMillimeters: number };",
+		));
+	}
+}
@@ -0,0 +1,358 @@
+//! Checking values against the schema at runtime, so a server that drifts
+//! from the schema fails where the response is read instead of deep in
+//! the code using it.
+//!
+//! Values are checked in the wire format, before they're decoded and
+//! after they're encoded, and the paths and messages are the same as the
+//! server's [`check_value`](core_types::value::check_value).
+//!
+//! ```typescript
+//! export function validateUser(
+//! 	value: any,
+//! 	path: string,
+//! 	errors: ValidationIssue[],
+//! ): void {
+//! 	if (kind(value) !== "object") {
+//! 		errors.push(mismatch("User", value, path));
+//! 		return;
+//! 	}
+//! 	validateInt(value.id, `${path}.id`, errors);
+//! 	validateOptional(validateUser)(value.manager, `${path}.manager`, errors);
+//! }
+//! ```
+use core_types::{naming::pascal_case, Enum, IndexMap, SchemaType};
+use super::{ast::{Expr, Function, Key, Property, Stmt, TemplatePart}, Field, TsType};
+
+/// The name of the function validating the type, e.g. `validateUser`.
+pub fn function_name(type_name: &str) -> String{
+	format!("validate{}",pascal_case(type_name))
+}
+
+/// An expression for the `Validator` of the `_type`.
+pub fn validator(_type: &SchemaType) -> Expr{
+	match _type {
+		SchemaType::Int => Expr::ident("validateInt"),
+		SchemaType::Float => Expr::ident("validateFloat"),
+		SchemaType::String => Expr::ident("validateString"),
+		SchemaType::Boolean => Expr::ident("validateBoolean"),
+		SchemaType::Optional(inner) => Expr::ident("validateOptional").call(vec![validator(inner)]),
+		SchemaType::Array(inner) => Expr::ident("validateArray").call(vec![validator(inner)]),
+		SchemaType::Map(inner) => Expr::ident("validateMap").call(vec![validator(inner)]),
+		SchemaType::Struct(name) => Expr::ident(&function_name(name))
+	}
+}
+
+/// Check the `value` against the `_type`, the expression throws a
+/// `ValidationError` if it doesn't match and is the value otherwise.
+pub fn check(_type: &SchemaType, value: Expr) -> Expr{
+	Expr::ident("checkValue").call(vec![validator(_type),value])
+}
+
+/// `validate(value.field, `${path}.field`, errors);`
+fn validate_field(_type: &SchemaType, name: &str) -> Stmt{
+	let path = Expr::Template(vec![
+		TemplatePart::Expr(Expr::ident("path")),
+		TemplatePart::Text(format!(".{name}"))
+	]);
+	Stmt::Expr(validator(_type).call(vec![
+		Expr::ident("value").member(name),
+		path,
+		Expr::ident("errors")
+	]))
+}
+
+/// Report the value if it isn't an object, the `name` is the type that
+/// was expected.
+fn expect_object(name: &str) -> Stmt{
+	let mismatch = Expr::ident("mismatch").call(vec![
+		Expr::string(name),
+		Expr::ident("value"),
+		Expr::ident("path")
+	]);
+	Stmt::If{
+		condition: Expr::ident("kind")
+			.call(vec![Expr::ident("value")])
+			.binary("!==", Expr::string("object")),
+		then: vec![
+			Stmt::Expr(Expr::ident("errors").member("push").call(vec![mismatch])),
+			Stmt::Return(None)
+		]
+	}
+}
+
+/// A validator function with its signature filled in.
+fn function(name: &str, body: Vec<Stmt>) -> Function{
+	Function{
+		exported: true,
+		name: function_name(name),
+		params: vec![
+			Field::new("value", TsType::Any),
+			Field::new("path", TsType::String),
+			Field::new("errors", TsType::Array(Box::new(TsType::Custom(String::from("ValidationIssue")))))
+		],
+		returns: TsType::Void,
+		body
+	}
+}
+
+/// The validator of a struct, fields are checked in declaration order.
+pub fn struct_validator(name: &str, fields: &IndexMap<String,SchemaType>) -> Function{
+	let body = std::iter::once(expect_object(name))
+		.chain(fields.iter().map(|(field,_type)|validate_field(_type, field)))
+		.collect();
+	function(name, body)
+}
+
+/// The validator of an enum, tagged unions check the fields of the
+/// variant named by the tag.
+pub fn enum_validator(name: &str, value: &Enum) -> Function{
+	let body = match value {
+		Enum::Simple(values) => {
+			let values = Expr::Array(values.iter().map(|value|Expr::string(value)).collect());
+			let validate = Expr::ident("validateOneOf").call(vec![values]);
+			vec![Stmt::Expr(validate.call(vec![
+				Expr::ident("value"),
+				Expr::ident("path"),
+				Expr::ident("errors")
+			]))]
+		},
+		Enum::Tagged(union) => {
+			let cases = union.variants.iter()
+				.map(|(variant,fields)|{
+					let body = fields.iter()
+						.map(|(field,_type)|validate_field(_type, field))
+						.chain([Stmt::Return(None)])
+						.collect();
+					(Expr::string(variant),body)
+				})
+				.collect();
+
+			let variants: Vec<String> = union.variants.keys()
+				.map(|variant|format!("\"{variant}\""))
+				.collect();
+			let error = Expr::Object(vec![
+				Property{
+					key: Key::Name(String::from("path")),
+					value: Expr::Template(vec![
+						TemplatePart::Expr(Expr::ident("path")),
+						TemplatePart::Text(format!(".{}",union.tag))
+					])
+				},
+				Property{
+					key: Key::Name(String::from("message")),
+					value: Expr::string(&format!("expected one of {}",variants.join(", ")))
+				}
+			]);
+
+			vec![
+				expect_object(name),
+				Stmt::Switch{
+					value: Expr::ident("value").member(&union.tag),
+					cases,
+					default: vec![Stmt::Expr(Expr::ident("errors").member("push").call(vec![error]))]
+				}
+			]
+		}
+	};
+	function(name, body)
+}
+
+/// The error thrown when a value doesn't match the schema, and the
+/// validators the generated ones are built from.
+pub fn runtime() -> &'static str{
+	indoc::indoc!{r#"
+		/** A value that doesn't match its type in the schema. */
+		export interface ValidationIssue {
+			/** The path to the value, e.g. `$.items[0].id` */
+			path: string;
+			message: string;
+		}
+
+		/** Thrown when a request or response body doesn't match the schema. */
+		export class ValidationError extends Error {
+			errors: ValidationIssue[];
+
+			constructor(errors: ValidationIssue[]) {
+				super(errors.map((error) => `${error.path}: ${error.message}`).join(", "));
+				this.errors = errors;
+			}
+		}
+
+		/** Adds an issue to `errors` for every value that doesn't match. */
+		export type Validator = (
+			value: any,
+			path: string,
+			errors: ValidationIssue[],
+		) => void;
+
+		/** Check the value, throwing a `ValidationError` if it doesn't match. */
+		export function checkValue(validate: Validator, value: any): any {
+			const errors: ValidationIssue[] = [];
+			validate(value, "$", errors);
+			if (errors.length > 0) {
+				throw new ValidationError(errors);
+			}
+			return value;
+		}
+
+		function kind(value: any): string {
+			if (value === null) {
+				return "null";
+			}
+			if (Array.isArray(value)) {
+				return "array";
+			}
+			if (typeof value === "number") {
+				return Number.isInteger(value) ? "int" : "float";
+			}
+			return typeof value;
+		}
+
+		function mismatch(expected: string, value: any, path: string): ValidationIssue {
+			if (value === undefined) {
+				return { path, message: "missing field" };
+			}
+			return { path, message: `expected ${expected}, found ${kind(value)}` };
+		}
+
+		const validateInt: Validator = (value, path, errors) => {
+			if (kind(value) !== "int") {
+				errors.push(mismatch("int", value, path));
+			} else if (value < -2147483648 || value > 2147483647) {
+				errors.push({ path, message: `${value} is out of range for an int` });
+			}
+		};
+
+		const validateFloat: Validator = (value, path, errors) => {
+			if (typeof value !== "number") {
+				errors.push(mismatch("float", value, path));
+			}
+		};
+
+		const validateString: Validator = (value, path, errors) => {
+			if (typeof value !== "string") {
+				errors.push(mismatch("string", value, path));
+			}
+		};
+
+		const validateBoolean: Validator = (value, path, errors) => {
+			if (typeof value !== "boolean") {
+				errors.push(mismatch("boolean", value, path));
+			}
+		};
+
+		function validateOptional(validate: Validator): Validator {
+			return (value, path, errors) => {
+				if (value != null) {
+					validate(value, path, errors);
+				}
+			};
+		}
+
+		function validateArray(validate: Validator): Validator {
+			return (value, path, errors) => {
+				if (!Array.isArray(value)) {
+					errors.push(mismatch("array", value, path));
+					return;
+				}
+				value.forEach((item: any, i: number) => {
+					validate(item, `${path}[${i}]`, errors);
+				});
+			};
+		}
+
+		function validateMap(validate: Validator): Validator {
+			return (value, path, errors) => {
+				if (kind(value) !== "object") {
+					errors.push(mismatch("map", value, path));
+					return;
+				}
+				for (const [key, item] of Object.entries(value)) {
+					validate(item, `${path}.${key}`, errors);
+				}
+			};
+		}
+
+		function validateOneOf(values: string[]): Validator {
+			const expected = values.map((value) => JSON.stringify(value)).join(", ");
+			return (value, path, errors) => {
+				if (value === undefined) {
+					errors.push({ path, message: "missing field" });
+				} else if (!values.includes(value)) {
+					const found = JSON.stringify(value);
+					const message = `expected one of ${expected}, found ${found}`;
+					errors.push({ path, message });
+				}
+			};
+		}"#
+	}
+}
+
+#[cfg(test)]
+mod tests{
+	use core_types::TaggedUnion;
+	use super::*;
+
+	#[test]
+	fn nested_validators(){
+		let _type: SchemaType = "map<string, User[]>?".parse().unwrap();
+		assert_eq!(
+			validator(&_type).to_string(),
+			"validateOptional(validateMap(validateArray(validateUser)))"
+		);
+		assert_eq!(
+			check(&SchemaType::String, Expr::ident("body")).to_string(),
+			"checkValue(validateString, body)"
+		);
+	}
+
+	#[test]
+	fn struct_function(){
+		let fields = IndexMap::from([
+			("created_at".to_string(),SchemaType::String),
+			("tags".to_string(),SchemaType::Array(Box::new(SchemaType::String))),
+		]);
+
+		assert_eq!(struct_validator("user", &fields).to_string(),concat!(
+			"export function validateUser(\n",
+			"\tvalue: any,\n",
+			"\tpath: string,\n",
+			"\terrors: ValidationIssue[],\n",
+			"): void {\n",
+			"\tif (kind(value) !== \"object\") {\n",
+			"\t\terrors.push(mismatch(\"user\", value, path));\n",
+			"\t\treturn;\n",
+			"\t}\n",
+			"\tvalidateString(value.created_at, `${path}.created_at`, errors);\n",
+			"\tvalidateArray(validateString)(value.tags, `${path}.tags`, errors);\n",
+			"}",
+		));
+	}
+
+	#[test]
+	fn enum_functions(){
+		let status = Enum::Simple(vec!["active".to_string(),"on_hold".to_string()]);
+		assert!(enum_validator("status", &status).to_string().contains(
+			"\tvalidateOneOf([\"active\", \"on_hold\"])(value, path, errors);\n"
+		));
+
+		let shape = Enum::Tagged(TaggedUnion{
+			tag: "kind".to_string(),
+			variants: IndexMap::from([
+				("circle".to_string(),IndexMap::from([("radius".to_string(),SchemaType::Float)])),
+			])
+		});
+		assert!(enum_validator("shape", &shape).to_string().contains(concat!(
+			"\tswitch (value.kind) {\n",
+			"\t\tcase \"circle\":\n",
+			"\t\t\tvalidateFloat(value.radius, `${path}.radius`, errors);\n",
+			"\t\t\treturn;\n",
+			"\t\tdefault:\n",
+			"\t\t\terrors.push({\n",
+			"\t\t\t\tpath: `${path}.kind`,\n",
+			"\t\t\t\tmessage: 'expected one of \"circle\"',\n",
+			"\t\t\t});\n",
+			"\t}\n",
+		)));
+	}
+}
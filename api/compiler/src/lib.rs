@@ -4,25 +4,173 @@
 //! have valid types
 mod lang;
 mod error;
+mod response;
+mod query;
+pub mod diff;
 pub use error::{Error,Result};
+pub use response::{ResponseValidationLayer, ResponseMiddleware, OnInvalidResponse};
+pub use query::Query;
 pub use lang::*;
-use axum::{body::Body, extract::Request, response::Response};
+pub use core_types::{ApiSchema, Version, SchemaDiff, Rejection, RejectionCode};
+/// Used by the generated client to decode error bodies.
+#[doc(hidden)]
+pub use serde_json;
+use std::sync::Arc;
+use axum::{body::Body, extract::Request, response::{IntoResponse, Response}, Json};
+use core_types::{value::check_value, FieldError, SchemaType};
 use futures_util::future::BoxFuture;
 use http::StatusCode;
+use http_body_util::LengthLimitError;
 use tower::{Layer, Service};
 
 /// Validated that incoming requests are using the same schema
+///
+/// Requests are accepted when their checksum matches, or when the layer
+/// has a [`version`](ValidationLayer::version) and the client's version
+/// is within the allowed [`SchemaDiff`].
+/// 
+/// With [`validate_requests`](ValidationLayer::validate_requests) the 
+/// request bodies are also checked against the endpoint's input.
 #[derive(Clone)]
 pub struct ValidationLayer{
-	checksum: String
+	checksum: String,
+	header: String,
+	version_header: String,
+	version: Option<(Version,SchemaDiff)>,
+	schema: Option<Arc<ApiSchema>>
 }
 
+/// The largest request body that is buffered for validation.
+const BODY_LIMIT: usize = 2 * 1024 * 1024;
+
 impl ValidationLayer{
+	/// Create a layer that expects the checksum in the 
+	/// [`CHECKSUM_HEADER`](core_types::CHECKSUM_HEADER).
 	pub fn new(checksum:&str) -> Self{
 		Self{
-			checksum: String::from(checksum)
+			checksum: String::from(checksum),
+			header: String::from(core_types::CHECKSUM_HEADER),
+			version_header: String::from(core_types::VERSION_HEADER),
+			version: None,
+			schema: None
+		}
+	}
+
+	/// Set the header that the checksum is read from.
+	pub fn header(mut self, header: &str) -> Self{
+		self.header = String::from(header);
+		self
+	}
+
+	/// Set the header that the client's version is read from, defaults to
+	/// the [`VERSION_HEADER`](core_types::VERSION_HEADER).
+	pub fn version_header(mut self, header: &str) -> Self{
+		self.version_header = String::from(header);
+		self
+	}
+
+	/// Accept requests with a different checksum as long as the version
+	/// in the [`version_header`](ValidationLayer::version_header) is within
+	/// `schema_diff` of the server's `version`.
+	pub fn version(mut self, version: Version, schema_diff: SchemaDiff) -> Self{
+		self.version = Some((version,schema_diff));
+		self
+	}
+
+	/// Check the json body of requests to the schema's endpoints against
+	/// the endpoint's input, rejecting invalid bodies with every field
+	/// that doesn't match before the handler runs.
+	pub fn validate_requests(mut self, schema: ApiSchema) -> Self{
+		self.schema = Some(Arc::new(schema));
+		self
+	}
+
+	/// Create a layer that validates requests against the checksum and
+	/// version of the schema file, the same ones the generated clients send.
+	pub fn from_schema(path: impl AsRef<std::path::Path>) -> crate::Result<Self>{
+		let (schema,_) = ApiSchema::load(path)?;
+		let layer = Self::new(&schema.checksum())
+			.header(schema.checksum_header())
+			.version_header(schema.version_header())
+			.version(schema.version, schema.schema_diff);
+		Ok(layer)
+	}
+
+	/// Check the request's headers, returning the rejection if the
+	/// request is using an incompatible schema.
+	fn check(&self, headers: &http::HeaderMap) -> Option<Rejection>{
+		let Some(checksum) = headers.get(&self.header) else {
+			return Some(Rejection::new(
+				RejectionCode::MissingChecksum, 
+				&format!("the request is missing the {} header",self.header)
+			));
+		};
+		let Ok(checksum) = checksum.to_str() else {
+			return Some(Rejection::new(
+				RejectionCode::InvalidChecksum, 
+				&format!("the {} header is not valid ascii",self.header)
+			));
+		};
+		if checksum == self.checksum{
+			return None;
 		}
+
+		let client_version = headers.get(&self.version_header)
+			.and_then(|value|value.to_str().ok())
+			.and_then(|value|value.parse::<Version>().ok());
+
+		if let (Some((server,schema_diff)),Some(client)) = (&self.version,&client_version)
+			&& schema_diff.allows(server, client)
+		{
+			return None;
+		}
+
+		let mut rejection = Rejection::new(
+			RejectionCode::IncompatibleSchema,
+			"the client's schema is not compatible with the server's schema"
+		);
+		rejection.expected_checksum = Some(self.checksum.clone());
+		rejection.received_checksum = Some(String::from(checksum));
+		rejection.server_version = self.version.map(|(version,_)|version);
+		rejection.client_version = client_version;
+		Some(rejection)
+	}
+
+	/// The input type of the endpoint that the request is sent to, if the
+	/// request bodies are validated.
+	fn input(&self, method: &http::Method, path: &str) -> Option<(Arc<ApiSchema>,SchemaType)>{
+		let schema = self.schema.as_ref()?;
+		let (_,endpoint) = schema.find_endpoint(method.as_str(), path)?;
+		let input = endpoint.input.clone().filter(|_|endpoint.method.has_request_body())?;
+		Some((schema.clone(),input))
+	}
+}
+
+/// Check the json body against the type, returning the rejection if
+/// it doesn't match.
+fn check_body(schema: &ApiSchema, input: &SchemaType, body: &[u8]) -> Option<Rejection>{
+	let message = format!("the request body does not match {}",input);
+	let errors = match serde_json::from_slice(body) {
+		Ok(value) => check_value(schema, input, &value),
+		Err(error) => vec![FieldError{
+			path: String::from("$"),
+			message: format!("invalid json: {}",error)
+		}]
+	};
+	if errors.is_empty(){
+		return None;
 	}
+
+	let mut rejection = Rejection::new(RejectionCode::InvalidBody, &message);
+	rejection.errors = errors;
+	Some(rejection)
+}
+
+/// Respond with the rejection as the json body.
+fn reject(rejection: Rejection) -> Response{
+	let status = StatusCode::from_u16(rejection.code.status())
+		.unwrap_or(StatusCode::BAD_REQUEST);
+	(status,Json(rejection)).into_response()
 }
 
 impl<S> Layer<S> for ValidationLayer{
@@ -30,7 +178,7 @@ impl<S> Layer<S> for ValidationLayer{
 	fn layer(&self, inner: S) -> Self::Service {
 		SchemaMiddleware{
 			inner,
-			checksum: self.checksum.clone()
+			layer: self.clone()
 		}
 	}
 }
@@ -38,12 +186,12 @@ impl<S> Layer<S> for ValidationLayer{
 #[derive(Clone)]
 pub struct SchemaMiddleware<S>{
 	inner: S,
-	checksum: String
+	layer: ValidationLayer
 }
 
 impl<S> Service<Request> for SchemaMiddleware<S>  
 where 
-	S: Service<Request,Response = Response> + Send + 'static,
+	S: Service<Request,Response = Response> + Clone + Send + 'static,
 	S::Future: Send + 'static
 {
 	type Response = S::Response;
@@ -59,33 +207,41 @@ where
 	}
 
 	fn call(&mut self, req: Request) -> Self::Future {
-		match req.headers().get("Api-Schema-Checksum"){
-			Some(value) => {
-				let checksum = value.to_str().unwrap();
-				if checksum != self.checksum{
-					let response  = Response::builder()
-						.status(StatusCode::UNPROCESSABLE_ENTITY)
-						.body(Body::empty())
-						.unwrap();
-
-					return Box::pin(async move{Ok(response)});
-				}
-			},
-			None => {
-				let response  = Response::builder()
-					.status(StatusCode::BAD_REQUEST)
-					.body(Body::empty())
-					.unwrap();
-
-				return Box::pin(async move{Ok(response)});
-			}
+		if let Some(rejection) = self.layer.check(req.headers()){
+			return Box::pin(async move{Ok(reject(rejection))});
 		}
 
-		let future = self.inner.call(req);
-		
+		let Some((schema,input)) = self.layer.input(req.method(), req.uri().path()) else {
+			let future = self.inner.call(req);
+			return Box::pin(async move {
+				let resposnse = future.await?;
+				Ok(resposnse)
+			});
+		};
+
+		// The ready service has to be the one that's called
+		let clone = self.inner.clone();
+		let mut inner = std::mem::replace(&mut self.inner, clone);
 		Box::pin(async move {
-			let resposnse = future.await?;
-			Ok(resposnse)
+			let (parts,body) = req.into_parts();
+			let body = match axum::body::to_bytes(body, BODY_LIMIT).await {
+				Ok(body) => body,
+				Err(error) => {
+					let rejection = if error.into_inner().is::<LengthLimitError>(){
+						let message = format!("the request body is larger than {} bytes",BODY_LIMIT);
+						Rejection::new(RejectionCode::BodyTooLarge, &message)
+					} else {
+						Rejection::new(RejectionCode::InvalidBody, "the request body could not be read")
+					};
+					return Ok(reject(rejection));
+				}
+			};
+			if let Some(rejection) = check_body(&schema, &input, &body){
+				return Ok(reject(rejection));
+			}
+
+			let req = Request::from_parts(parts, Body::from(body));
+			inner.call(req).await
 		})
 	}
 	
@@ -93,9 +249,14 @@ where
 
 #[cfg(test)]
 mod tests{
-    use axum::{body::Body, routing::get, Router};
+    use axum::{routing::{get, post}, Router};
 	use super::*;
 
+	async fn rejection(response: Response) -> Rejection{
+		let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+		Rejection::parse(std::str::from_utf8(&body).unwrap()).unwrap()
+	}
+
 	#[tokio::test]
 	async fn api_validation(){
 
@@ -104,7 +265,7 @@ mod tests{
 			.layer(ValidationLayer::new("1234abc"));
 
 		let request = Request::builder()
-			.header("Api-Schema-Checksum", "1234abc")
+			.header(core_types::CHECKSUM_HEADER, "1234abc")
 			.uri("/")
 			.body(Body::empty())
 			.unwrap();
@@ -122,7 +283,7 @@ mod tests{
 			.layer(ValidationLayer::new("hi"));
 
 		let request = Request::builder()
-			.header("Api-Schema-Checksum", "kdkfs")
+			.header(core_types::CHECKSUM_HEADER, "kdkfs")
 			.uri("/")
 			.body(Body::empty())
 			.unwrap();
@@ -132,6 +293,70 @@ mod tests{
 		assert_eq!(response.status(),StatusCode::UNPROCESSABLE_ENTITY);
 	}
 
+	#[tokio::test]
+	async fn compatible_version(){
+		let layer = ValidationLayer::new("hi").version(Version::new(1, 2, 0), SchemaDiff::Minor);
+		let mut router = Router::new()
+			.route("/", get(||async {"Hello world"}))
+			.layer(layer);
+
+		let request = Request::builder()
+			.header(core_types::CHECKSUM_HEADER, "kdkfs")
+			.header(core_types::VERSION_HEADER, "1.1.4")
+			.uri("/")
+			.body(Body::empty())
+			.unwrap();
+
+		let response = router.call(request).await.unwrap();
+
+		assert_eq!(response.status(),StatusCode::OK);
+	}
+
+	#[tokio::test]
+	async fn custom_version_header(){
+		let layer = ValidationLayer::new("hi")
+			.version(Version::new(1, 2, 0), SchemaDiff::Minor)
+			.version_header("X-Version");
+		let router = Router::new()
+			.route("/", get(||async {"Hello world"}))
+			.layer(layer);
+
+		for (header,status) in [("X-Version",StatusCode::OK),(core_types::VERSION_HEADER,StatusCode::UNPROCESSABLE_ENTITY)]{
+			let request = Request::builder()
+				.header(core_types::CHECKSUM_HEADER, "kdkfs")
+				.header(header, "1.1.4")
+				.uri("/")
+				.body(Body::empty())
+				.unwrap();
+			assert_eq!(router.clone().call(request).await.unwrap().status(),status);
+		}
+	}
+
+	#[tokio::test]
+	async fn incompatible_version(){
+		let layer = ValidationLayer::new("hi").version(Version::new(1, 2, 0), SchemaDiff::Patch);
+		let mut router = Router::new()
+			.route("/", get(||async {"Hello world"}))
+			.layer(layer);
+
+		let request = Request::builder()
+			.header(core_types::CHECKSUM_HEADER, "kdkfs")
+			.header(core_types::VERSION_HEADER, "1.1.4")
+			.uri("/")
+			.body(Body::empty())
+			.unwrap();
+
+		let response = router.call(request).await.unwrap();
+		assert_eq!(response.status(),StatusCode::UNPROCESSABLE_ENTITY);
+
+		let rejection = rejection(response).await;
+		assert_eq!(rejection.code,RejectionCode::IncompatibleSchema);
+		assert_eq!(rejection.expected_checksum.as_deref(),Some("hi"));
+		assert_eq!(rejection.received_checksum.as_deref(),Some("kdkfs"));
+		assert_eq!(rejection.server_version,Some(Version::new(1, 2, 0)));
+		assert_eq!(rejection.client_version,Some(Version::new(1, 1, 4)));
+	}
+
 	#[tokio::test]
 	async fn missing_header(){
 		let mut router = Router::new()
@@ -146,7 +371,107 @@ mod tests{
 		let response = router.call(request).await.unwrap();
 
 		assert_eq!(response.status(),StatusCode::BAD_REQUEST);
+		assert_eq!(rejection(response).await.code,RejectionCode::MissingChecksum);
 	}
-}
 
+	#[tokio::test]
+	async fn non_ascii_header(){
+		let mut router = Router::new()
+			.route("/", get(||async {"Hello world"}))
+			.layer(ValidationLayer::new("hi"));
+
+		let request = Request::builder()
+			.header(core_types::CHECKSUM_HEADER, http::HeaderValue::from_bytes(b"h\xffi").unwrap())
+			.uri("/")
+			.body(Body::empty())
+			.unwrap();
+
+		let response = router.call(request).await.unwrap();
+
+		assert_eq!(response.status(),StatusCode::BAD_REQUEST);
+		assert_eq!(rejection(response).await.code,RejectionCode::InvalidChecksum);
+	}
+
+	fn body_router() -> Router{
+		let schema = ApiSchema::parse(concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    name: string\n",
+			"    age: int\n",
+			"endpoints:\n",
+			"  update_user:\n",
+			"    uri: https://example.com/users/{id}\n",
+			"    method: PUT\n",
+			"    input: User\n",
+		)).unwrap();
+		let layer = ValidationLayer::new("hi").validate_requests(schema);
+		Router::new()
+			.route("/users/{id}", post(||async {"Created"}).put(|body: String|async {body}))
+			.layer(layer)
+	}
+
+	fn body_request(method: &str, uri: &str, body: &str) -> Request{
+		Request::builder()
+			.method(method)
+			.header(core_types::CHECKSUM_HEADER, "hi")
+			.uri(uri)
+			.body(Body::from(body.to_string()))
+			.unwrap()
+	}
+
+	#[tokio::test]
+	async fn valid_body(){
+		let body = r#"{"name":"Wanjiru","age":24}"#;
+		let response = body_router().call(body_request("PUT", "/users/5", body)).await.unwrap();
+		assert_eq!(response.status(),StatusCode::OK);
+
+		// The handler still gets the body
+		let bytes = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+		assert_eq!(bytes,body.as_bytes());
+	}
 
+	#[tokio::test]
+	async fn invalid_body(){
+		let request = body_request("PUT", "/users/5", r#"{"age":"24"}"#);
+		let response = body_router().call(request).await.unwrap();
+		assert_eq!(response.status(),StatusCode::UNPROCESSABLE_ENTITY);
+
+		let rejection = rejection(response).await;
+		assert_eq!(rejection.code,RejectionCode::InvalidBody);
+		let errors: Vec<_> = rejection.errors.iter()
+			.map(|error|(error.path.as_str(),error.message.as_str()))
+			.collect();
+		// In the order the fields are declared
+		assert_eq!(errors,vec![
+			("$.name","missing field"),
+			("$.age","expected int, found string"),
+		]);
+	}
+
+	#[tokio::test]
+	async fn invalid_json_body(){
+		let response = body_router().call(body_request("PUT", "/users/5", "{")).await.unwrap();
+		assert_eq!(response.status(),StatusCode::UNPROCESSABLE_ENTITY);
+
+		let rejection = rejection(response).await;
+		assert_eq!(rejection.errors.len(),1);
+		assert_eq!(rejection.errors[0].path,"$");
+	}
+
+	#[tokio::test]
+	async fn body_too_large(){
+		let body = " ".repeat(BODY_LIMIT + 1);
+		let response = body_router().call(body_request("PUT", "/users/5", &body)).await.unwrap();
+		assert_eq!(response.status(),StatusCode::PAYLOAD_TOO_LARGE);
+		assert_eq!(rejection(response).await.code,RejectionCode::BodyTooLarge);
+	}
+
+	#[tokio::test]
+	async fn unknown_endpoint_body(){
+		// Only the endpoints in the schema are validated
+		let response = body_router().call(body_request("POST", "/users/5", "{")).await.unwrap();
+		assert_eq!(response.status(),StatusCode::OK);
+	}
+}
@@ -0,0 +1,61 @@
+//! Extracting query string parameters in the generated router.
+use axum::{extract::FromRequestParts, response::{IntoResponse, Response}};
+use http::{request::Parts, StatusCode};
+use serde::de::DeserializeOwned;
+
+/// Extracts the query string like [`axum::extract::Query`], except that
+/// lists are read from repeated keys, e.g. `tags=a&tags=b`, which is how
+/// the generated clients send them.
+#[derive(Debug,Clone,Copy,Default)]
+pub struct Query<T>(pub T);
+
+impl<T,S> FromRequestParts<S> for Query<T>
+where
+	T: DeserializeOwned,
+	S: Send + Sync
+{
+	type Rejection = Response;
+
+	async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+		let query = parts.uri.query().unwrap_or_default();
+		match serde_html_form::from_str(query) {
+			Ok(value) => Ok(Self(value)),
+			Err(error) => {
+				let message = format!("Failed to deserialize query string: {}",error);
+				Err((StatusCode::BAD_REQUEST,message).into_response())
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests{
+	use serde::Deserialize;
+	use super::*;
+
+	#[derive(Debug,PartialEq,Deserialize)]
+	struct Search{
+		#[serde(default)]
+		tags: Vec<String>,
+		page: Option<i32>
+	}
+
+	async fn extract(uri: &str) -> Result<Search,StatusCode>{
+		let (mut parts,_) = http::Request::builder().uri(uri).body(()).unwrap().into_parts();
+		Query::<Search>::from_request_parts(&mut parts, &())
+			.await
+			.map(|Query(search)|search)
+			.map_err(|response|response.status())
+	}
+
+	#[tokio::test]
+	async fn repeated_keys(){
+		let search = extract("/orders?tags=a%2Cb&tags=c&page=2").await.unwrap();
+		assert_eq!(search,Search{tags: vec!["a,b".to_string(),"c".to_string()], page: Some(2)});
+
+		let search = extract("/orders").await.unwrap();
+		assert_eq!(search,Search{tags: vec![], page: None});
+
+		assert_eq!(extract("/orders?page=two").await,Err(StatusCode::BAD_REQUEST));
+	}
+}
@@ -0,0 +1,214 @@
+//! Checking the handlers' responses against the schema, to catch a server
+//! drifting from the schema before its clients do.
+use std::sync::Arc;
+use axum::{body::Body, extract::Request, response::{IntoResponse, Response}};
+use core_types::{value::check_value, ApiSchema, Rejection, RejectionCode};
+use futures_util::future::BoxFuture;
+use http::StatusCode;
+use tower::{Layer, Service};
+use crate::reject;
+
+/// What to do with a response that doesn't match the endpoint's return
+/// type.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub enum OnInvalidResponse{
+	/// Log the invalid values and send the response anyway
+	#[default]
+	Log,
+	/// Replace the response with a `500` [`Rejection`] listing the
+	/// invalid values
+	Error,
+	/// Don't check the responses
+	PassThrough
+}
+
+/// Checks the json body of successful responses against the `returns`
+/// type of the endpoint the request was sent to.
+///
+/// The responses are buffered before they're sent, so this is meant for
+/// development and staging builds.
+///
+/// ```ignore
+/// let layer = ResponseValidationLayer::new(schema())
+/// 	.on_invalid(OnInvalidResponse::Error);
+/// let app = router(Server).layer(layer);
+/// ```
+#[derive(Clone)]
+pub struct ResponseValidationLayer{
+	schema: Arc<ApiSchema>,
+	on_invalid: OnInvalidResponse
+}
+
+impl ResponseValidationLayer{
+	/// Create a layer that logs invalid responses.
+	pub fn new(schema: ApiSchema) -> Self{
+		Self{
+			schema: Arc::new(schema),
+			on_invalid: OnInvalidResponse::default()
+		}
+	}
+
+	/// Set what happens to responses that don't match the schema.
+	pub fn on_invalid(mut self, on_invalid: OnInvalidResponse) -> Self{
+		self.on_invalid = on_invalid;
+		self
+	}
+
+	/// Check the response, returning the response that should be sent.
+	async fn check(&self, name: &str, response: Response) -> Response{
+		let Some(returns) = self.schema.endpoints
+			.get(name)
+			.filter(|endpoint|endpoint.method.has_response_body())
+			.and_then(|endpoint|endpoint.returns.as_ref())
+		else {
+			return response;
+		};
+
+		let (parts,body) = response.into_parts();
+		let Ok(body) = axum::body::to_bytes(body, usize::MAX).await else {
+			tracing::error!(endpoint = name, "failed to read the response body");
+			return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+		};
+		let errors = match serde_json::from_slice(&body) {
+			Ok(value) => check_value(&self.schema, returns, &value),
+			Err(error) => vec![core_types::FieldError{
+				path: String::from("$"),
+				message: format!("invalid json: {}",error)
+			}]
+		};
+		if errors.is_empty(){
+			return Response::from_parts(parts, Body::from(body));
+		}
+
+		let message = format!("the response body of {} does not match {}",name,returns);
+		for error in &errors{
+			tracing::warn!(endpoint = name, path = error.path, "{}: {}",message,error.message);
+		}
+
+		match self.on_invalid {
+			OnInvalidResponse::Error => {
+				let mut rejection = Rejection::new(RejectionCode::InvalidResponse, &message);
+				rejection.errors = errors;
+				reject(rejection)
+			},
+			_ => Response::from_parts(parts, Body::from(body))
+		}
+	}
+}
+
+impl<S> Layer<S> for ResponseValidationLayer{
+	type Service = ResponseMiddleware<S>;
+	fn layer(&self, inner: S) -> Self::Service {
+		ResponseMiddleware{
+			inner,
+			layer: self.clone()
+		}
+	}
+}
+
+#[derive(Clone)]
+pub struct ResponseMiddleware<S>{
+	inner: S,
+	layer: ResponseValidationLayer
+}
+
+impl<S> Service<Request> for ResponseMiddleware<S>
+where
+	S: Service<Request,Response = Response> + Send + 'static,
+	S::Future: Send + 'static
+{
+	type Response = S::Response;
+	type Error = S::Error;
+	type Future = BoxFuture<'static,std::result::Result<Self::Response,Self::Error>>;
+
+	fn poll_ready(
+		&mut self,
+		cx: &mut std::task::Context<'_>
+	) -> std::task::Poll<std::result::Result<(), Self::Error>>
+	{
+		self.inner.poll_ready(cx)
+	}
+
+	fn call(&mut self, req: Request) -> Self::Future {
+		let name = match self.layer.on_invalid {
+			OnInvalidResponse::PassThrough => None,
+			_ => self.layer.schema
+				.find_endpoint(req.method().as_str(), req.uri().path())
+				.map(|(name,_)|name.clone())
+		};
+		let layer = self.layer.clone();
+		let future = self.inner.call(req);
+
+		Box::pin(async move {
+			let response = future.await?;
+			match name {
+				Some(name) if response.status().is_success() => Ok(layer.check(&name, response).await),
+				_ => Ok(response)
+			}
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests{
+	use axum::{routing::get, Json, Router};
+	use serde_json::json;
+	use super::*;
+
+	fn router(on_invalid: OnInvalidResponse) -> Router{
+		let schema = ApiSchema::parse(concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    name: string\n",
+			"endpoints:\n",
+			"  get_user:\n",
+			"    uri: https://example.com/users/{id}\n",
+			"    method: GET\n",
+			"    returns: User\n",
+			"  get_admin:\n",
+			"    uri: https://example.com/admin\n",
+			"    method: GET\n",
+			"    returns: User\n",
+		)).unwrap();
+		let layer = ResponseValidationLayer::new(schema).on_invalid(on_invalid);
+		Router::new()
+			.route("/users/{id}", get(||async {Json(json!({"name": 5}))}))
+			.route("/admin", get(||async {Json(json!({"name": "Jane"}))}))
+			.layer(layer)
+	}
+
+	async fn send(mut router: Router, uri: &str) -> (StatusCode,serde_json::Value){
+		let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+		let response = router.call(request).await.unwrap();
+		let status = response.status();
+		let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+		(status,serde_json::from_slice(&body).unwrap())
+	}
+
+	#[tokio::test]
+	async fn valid_response(){
+		let response = send(router(OnInvalidResponse::Error), "/admin").await;
+		assert_eq!(response,(StatusCode::OK,json!({"name": "Jane"})));
+	}
+
+	#[tokio::test]
+	async fn invalid_response_error(){
+		let (status,body) = send(router(OnInvalidResponse::Error), "/users/5").await;
+		assert_eq!(status,StatusCode::INTERNAL_SERVER_ERROR);
+
+		let rejection: Rejection = serde_json::from_value(body).unwrap();
+		assert_eq!(rejection.code,RejectionCode::InvalidResponse);
+		assert_eq!(rejection.errors[0].path,"$.name");
+		assert_eq!(rejection.errors[0].message,"expected string, found int");
+	}
+
+	#[tokio::test]
+	async fn invalid_response_sent(){
+		for on_invalid in [OnInvalidResponse::Log,OnInvalidResponse::PassThrough]{
+			let response = send(router(on_invalid), "/users/5").await;
+			assert_eq!(response,(StatusCode::OK,json!({"name": 5})));
+		}
+	}
+}
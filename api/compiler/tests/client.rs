@@ -0,0 +1,100 @@
+compiler::rs::code_gen!("tests/schemas/server.yml");
+
+#[derive(Clone)]
+struct Server;
+
+impl Api for Server{
+	async fn create_user(&self, payload: UserPayload) -> User{
+		User { id: 1, name: payload.name }
+	}
+
+	async fn get_user(&self, path: GetUserPath) -> Result<User,GetUserError>{
+		if path.id == 0{
+			let message = String::from("user 0 does not exist");
+			return Err(GetUserError::NotFound(NotFound { message }));
+		}
+		Ok(User { id: path.id, name: String::from("Jane") })
+	}
+
+	async fn list_users(&self, query: ListUsersQuery) -> Vec<User>{
+		let name = query.name.unwrap_or_default();
+		let tagged = query.tags.into_iter().map(|name|User { id: 2, name });
+		std::iter::once(User { id: 1, name }).chain(tagged).collect()
+	}
+
+	async fn delete_user(&self, _path: DeleteUserPath) -> Result<(),DeleteUserError>{
+		Ok(())
+	}
+}
+
+/// Serve the app on a random port, returning the base url
+async fn serve(app: axum::Router) -> String{
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let address = listener.local_addr().unwrap();
+	tokio::spawn(async move {
+		axum::serve(listener, app).await.unwrap();
+	});
+	format!("http://{}",address)
+}
+
+#[tokio::test]
+async fn client_requests(){
+	let client = Client::new(&serve(router(Server)).await);
+
+	let user = client.create_user(&UserPayload { name: String::from("Jane") }).await.unwrap();
+	assert_eq!((user.id,user.name.as_str()),(1,"Jane"));
+
+	let user = client.get_user(5).await.unwrap();
+	assert_eq!((user.id,user.name.as_str()),(5,"Jane"));
+
+	let query = ListUsersQuery { name: Some(String::from("John Doe")), tags: vec![] };
+	let users = client.list_users(query).await.unwrap();
+	assert_eq!(users[0].name,"John Doe");
+
+	let tags = vec![String::from("a,b"),String::from("c")];
+	let users = client.list_users(ListUsersQuery { name: None, tags: tags.clone() }).await.unwrap();
+	assert_eq!(users.into_iter().skip(1).map(|user|user.name).collect::<Vec<_>>(),tags);
+
+	client.delete_user(5).await.unwrap();
+}
+
+#[tokio::test]
+async fn client_base_url_with_path(){
+	let base_url = format!("{}/",serve(router(Server)).await);
+	let client = Client::new(&base_url);
+
+	assert_eq!(client.get_user(2).await.unwrap().id,2);
+}
+
+#[tokio::test]
+async fn client_declared_errors(){
+	let client = Client::new(&serve(router(Server)).await);
+
+	let Err(ClientError::Api(GetUserError::NotFound(error))) = client.get_user(0).await else {
+		panic!("Expected a not found error");
+	};
+	assert_eq!(error.message,"user 0 does not exist");
+}
+
+#[tokio::test]
+async fn client_checksum_mismatch(){
+	// A server for a different version of the schema
+	let app = axum::Router::new()
+		.route("/users/{id}", axum::routing::get(||async {}))
+		.layer(compiler::ValidationLayer::new("other"));
+	let client = Client::new(&serve(app).await);
+
+	let Err(ClientError::Rejected(rejection)) = client.get_user(5).await else {
+		panic!("Expected the request to be rejected");
+	};
+	assert_eq!(rejection.code,compiler::RejectionCode::IncompatibleSchema);
+	assert_eq!(rejection.expected_checksum.as_deref(),Some("other"));
+	assert_eq!(rejection.received_checksum.as_deref(),Some(SCHEMA_CHECKSUM));
+}
+
+#[tokio::test]
+async fn client_invalid_base_url(){
+	let client = Client::new("not a url");
+
+	assert!(matches!(client.get_user(5).await,Err(ClientError::Url(_))));
+}
@@ -0,0 +1,50 @@
+compiler::rs::code_gen!("tests/schemas/codegen.yml");
+
+#[derive(Clone)]
+struct Server;
+
+impl Api for Server{
+	async fn get_user(&self, path: GetUserPath) -> User{
+		User { id: path.id, name: String::from("Jane") }
+	}
+}
+
+#[tokio::test]
+async fn rust_client_name(){
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let address = listener.local_addr().unwrap();
+	tokio::spawn(async move {
+		axum::serve(listener, router(Server)).await.unwrap();
+	});
+
+	let client = ShopApi::new(&format!("http://{}",address));
+	assert_eq!(client.get_user(3).await.unwrap().id,3);
+	assert_eq!(BASE_URL,"https://api.example.com/v1");
+	assert!(format!("{:?}",ShopApi::default()).contains(BASE_URL));
+}
+
+#[test]
+fn typescript_output(){
+	let directory = std::env::temp_dir().join("typescript_output");
+	std::fs::create_dir_all(&directory).unwrap();
+	let schema_path = directory.join("codegen.yml");
+	std::fs::copy("tests/schemas/codegen.yml", &schema_path).unwrap();
+
+	let output_path = compiler::ts::generate(&schema_path).unwrap();
+	assert_eq!(output_path,directory.join("client.ts"));
+
+	let contents = std::fs::read_to_string(output_path).unwrap();
+	assert!(contents.contains("export class ShopClient {"));
+	assert!(contents.contains("export const BASE_URL = \"https://api.example.com/v1\";"));
+	assert!(contents.contains("constructor(options?: ClientOptions) {"));
+	assert!(contents.contains("this.baseUrl = options?.baseUrl ?? BASE_URL;"));
+	assert!(contents.contains("this.headers = options?.headers ?? {};"));
+	assert!(contents.contains("const url = `${this.baseUrl}/users/${encodeURIComponent(String(id))}`;"));
+}
+
+#[test]
+fn typescript_missing_output(){
+	let result = compiler::ts::generate("tests/schemas/header.yml");
+
+	assert!(matches!(result,Err(compiler::Error::MissingOutput(_))));
+}
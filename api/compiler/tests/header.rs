@@ -0,0 +1,124 @@
+use axum::{body::Body, extract::Request};
+use http::StatusCode;
+use tower::ServiceExt;
+
+compiler::rs::code_gen!("tests/schemas/header.yml");
+
+#[derive(Clone)]
+struct Server;
+
+impl Api for Server{
+	async fn get_user(&self, path: GetUserPath) -> User{
+		User { id: path.id, name: String::from("Jane") }
+	}
+}
+
+/// Serve the app on a random port, returning the base url
+async fn serve(app: axum::Router) -> String{
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let address = listener.local_addr().unwrap();
+	tokio::spawn(async move {
+		axum::serve(listener, app).await.unwrap();
+	});
+	format!("http://{}",address)
+}
+
+/// Read the value of an exported string constant from the generated typescript
+fn ts_constant(contents: &str, name: &str) -> String{
+	let prefix = format!("export const {} = \"",name);
+	let start = contents.find(&prefix).unwrap() + prefix.len();
+	let end = start + contents[start..].find('"').unwrap();
+	contents[start..end].to_string()
+}
+
+async fn status(header: &str, checksum: &str) -> StatusCode{
+	let request = Request::builder()
+		.uri("/users/5")
+		.header(header, checksum)
+		.body(Body::empty())
+		.unwrap();
+
+	router(Server).oneshot(request).await.unwrap().status()
+}
+
+/// Send a request from a client with an older schema
+async fn versioned_status(version: &str) -> StatusCode{
+	versioned_status_in(VERSION_HEADER, version).await
+}
+
+async fn versioned_status_in(header: &str, version: &str) -> StatusCode{
+	let request = Request::builder()
+		.uri("/users/5")
+		.header(CHECKSUM_HEADER, "old")
+		.header(header, version)
+		.body(Body::empty())
+		.unwrap();
+
+	router(Server).oneshot(request).await.unwrap().status()
+}
+
+#[tokio::test]
+async fn typescript_client_headers(){
+	let directory = std::env::temp_dir().join("typescript_client_headers");
+	std::fs::create_dir_all(&directory).unwrap();
+	let output_path = directory.join("types.ts");
+	compiler::ts::codegen("tests/schemas/header.yml", output_path.to_str().unwrap()).unwrap();
+	let contents = std::fs::read_to_string(output_path).unwrap();
+
+	// Send the headers the typescript client would send
+	let header = ts_constant(&contents, "CHECKSUM_HEADER");
+	let checksum = ts_constant(&contents, "SCHEMA_CHECKSUM");
+	assert!(contents.contains("[CHECKSUM_HEADER]: this.checksum,"));
+	assert_eq!(header,"X-Schema-Checksum");
+	assert_eq!(ts_constant(&contents, "VERSION_HEADER"),"X-Schema-Version");
+	assert_eq!(status(&header, &checksum).await,StatusCode::OK);
+	
+	assert_eq!(status(core_types::CHECKSUM_HEADER, &checksum).await,StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn rust_client_headers(){
+	let client = Client::new(&serve(router(Server)).await);
+	assert_eq!(client.get_user(3).await.unwrap().id,3);
+
+	// The layer the server would create from the schema file
+	let layer = compiler::ValidationLayer::from_schema("tests/schemas/header.yml").unwrap();
+	let client = Client::new(&serve(router_with_layer(Server, layer)).await);
+	assert_eq!(client.get_user(4).await.unwrap().id,4);
+
+	// A layer that expects the checksum in the default header
+	let layer = compiler::ValidationLayer::new(SCHEMA_CHECKSUM);
+	let client = Client::new(&serve(router_with_layer(Server, layer)).await);
+	let Err(ClientError::Rejected(rejection)) = client.get_user(5).await else {
+		panic!("Expected the request to be rejected");
+	};
+	assert_eq!(rejection.code,compiler::RejectionCode::MissingChecksum);
+}
+
+#[tokio::test]
+async fn layer_from_schema(){
+	let layer = compiler::ValidationLayer::from_schema("tests/schemas/header.yml").unwrap();
+	let app = axum::Router::new()
+		.route("/", axum::routing::get(||async {}))
+		.layer(layer);
+
+	let request = Request::builder()
+		.uri("/")
+		.header(CHECKSUM_HEADER, SCHEMA_CHECKSUM)
+		.body(Body::empty())
+		.unwrap();
+	assert_eq!(app.oneshot(request).await.unwrap().status(),StatusCode::OK);
+}
+
+#[tokio::test]
+async fn version_negotiation(){
+	assert_eq!(SCHEMA_VERSION,"1.2.0");
+	assert_eq!(versioned_status("1.1.7").await,StatusCode::OK);
+	assert_eq!(versioned_status("1.2.0").await,StatusCode::OK);
+	// Newer clients could call endpoints this server doesn't have
+	assert_eq!(versioned_status("1.2.1").await,StatusCode::UNPROCESSABLE_ENTITY);
+	assert_eq!(versioned_status("1.3.0").await,StatusCode::UNPROCESSABLE_ENTITY);
+	assert_eq!(versioned_status_in(core_types::VERSION_HEADER, "1.1.7").await,StatusCode::UNPROCESSABLE_ENTITY);
+	assert_eq!(versioned_status("0.2.0").await,StatusCode::UNPROCESSABLE_ENTITY);
+	assert_eq!(versioned_status("latest").await,StatusCode::UNPROCESSABLE_ENTITY);
+}
@@ -0,0 +1,60 @@
+use axum::{body::Body, extract::Request};
+use http::StatusCode;
+use serde_json::json;
+use tower::ServiceExt;
+
+compiler::rs::code_gen!("tests/schemas/naming.yml");
+
+#[derive(Clone)]
+struct Server;
+
+impl Api for Server{
+	async fn get_profile(&self, path: GetProfilePath, query: GetProfileQuery) -> UserProfile{
+		UserProfile{
+			created_at: path.profile_id.to_string(),
+			display_name: query.page_size.map(|size|size.to_string()),
+			status: AccountStatus::OnHold
+		}
+	}
+}
+
+#[test]
+fn renamed_fields(){
+	let profile = UserProfile{
+		created_at: String::from("today"),
+		display_name: None,
+		status: AccountStatus::Active
+	};
+	let value = json!({"createdAt": "today", "status": "active"});
+
+	assert_eq!(serde_json::to_value(&profile).unwrap(),value);
+	assert_eq!(serde_json::from_value::<UserProfile>(value).unwrap(),profile);
+
+	let value = json!({"createdAt": "today", "displayName": null, "status": "active"});
+	assert_eq!(serde_json::from_value::<UserProfile>(value).unwrap(),profile);
+}
+
+#[test]
+fn renamed_variants(){
+	assert_eq!(serde_json::to_value(AccountStatus::OnHold).unwrap(),json!("on_hold"));
+
+	let shape = Shape::Circle{radius_mm: 2.0};
+	let value = json!({"shapeKind": "circle", "radiusMm": 2.0});
+	assert_eq!(serde_json::to_value(&shape).unwrap(),value);
+	assert_eq!(serde_json::from_value::<Shape>(value).unwrap(),shape);
+}
+
+#[tokio::test]
+async fn renamed_params(){
+	let request = Request::builder()
+		.uri("/profiles/5?pageSize=20")
+		.header(CHECKSUM_HEADER, SCHEMA_CHECKSUM)
+		.body(Body::empty())
+		.unwrap();
+	let response = router(Server).oneshot(request).await.unwrap();
+	assert_eq!(response.status(),StatusCode::OK);
+
+	let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+	let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+	assert_eq!(body,json!({"createdAt": "5", "displayName": "20", "status": "on_hold"}));
+}
@@ -0,0 +1,37 @@
+compiler::rs::code_gen!("tests/schemas/prelude.yml");
+
+#[derive(Clone)]
+struct Server;
+
+impl Api for Server{
+	async fn get_result(&self, path: GetResultPath, query: GetResultQuery) -> std::result::Result<Result,GetResultError>{
+		Ok(Result{
+			id: path.id,
+			tags: query.tags,
+			note: query.note,
+			scores: std::collections::HashMap::new()
+		})
+	}
+
+	async fn create_results(&self, payload: Vec) -> Box{
+		Box { value: payload.results.len() as i32 }
+	}
+}
+
+#[tokio::test]
+async fn prelude_names(){
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let address = listener.local_addr().unwrap();
+	tokio::spawn(async move {
+		axum::serve(listener, router(Server)).await.unwrap();
+	});
+	let client = Client::new(&format!("http://{}",address));
+
+	let query = GetResultQuery{tags: vec![String::from("a")], note: None};
+	let result = client.get_result(5, query).await.unwrap();
+	assert_eq!(result.id,5);
+	assert_eq!(result.tags,vec!["a"]);
+
+	let payload = Vec{results: vec![result]};
+	assert_eq!(client.create_results(&payload).await.unwrap().value,1);
+}
@@ -0,0 +1,177 @@
+use axum::{body::Body, extract::Request};
+use http::StatusCode;
+use serde_json::json;
+use tower::ServiceExt;
+
+compiler::rs::code_gen!("tests/schemas/server.yml");
+
+#[derive(Clone)]
+struct Server;
+
+impl Api for Server{
+	async fn create_user(&self, payload: UserPayload) -> User{
+		User { id: 1, name: payload.name }
+	}
+
+	async fn get_user(&self, path: GetUserPath) -> Result<User,GetUserError>{
+		if path.id == 0{
+			let message = String::from("user 0 does not exist");
+			return Err(GetUserError::NotFound(NotFound { message }));
+		}
+		Ok(User { id: path.id, name: String::from("Jane") })
+	}
+
+	async fn list_users(&self, query: ListUsersQuery) -> Vec<User>{
+		let name = query.name.unwrap_or_default();
+		let tagged = query.tags.into_iter().map(|name|User { id: 2, name });
+		std::iter::once(User { id: 1, name }).chain(tagged).collect()
+	}
+
+	async fn delete_user(&self, _path: DeleteUserPath) -> Result<(),DeleteUserError>{
+		Ok(())
+	}
+}
+
+async fn send(request: Request) -> (StatusCode,serde_json::Value){
+	let response = router(Server)
+		.oneshot(request)
+		.await
+		.unwrap();
+
+	let status = response.status();
+	let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+	let body = serde_json::from_slice(&body).unwrap_or_default();
+	(status,body)
+}
+
+#[tokio::test]
+async fn router_post_json(){
+	let request = Request::builder()
+		.method("POST")
+		.uri("/users")
+		.header(CHECKSUM_HEADER, SCHEMA_CHECKSUM)
+		.header("Content-Type", "application/json")
+		.body(Body::from(r#"{"name":"Jane"}"#))
+		.unwrap();
+
+	assert_eq!(send(request).await,(StatusCode::OK,json!({"id":1,"name":"Jane"})));
+}
+
+#[tokio::test]
+async fn router_path_and_query(){
+	let request = Request::builder()
+		.uri("/users/5")
+		.header(CHECKSUM_HEADER, SCHEMA_CHECKSUM)
+		.body(Body::empty())
+		.unwrap();
+	assert_eq!(send(request).await,(StatusCode::OK,json!({"id":5,"name":"Jane"})));
+
+	let request = Request::builder()
+		.uri("/users?name=John")
+		.header(CHECKSUM_HEADER, SCHEMA_CHECKSUM)
+		.body(Body::empty())
+		.unwrap();
+	assert_eq!(send(request).await,(StatusCode::OK,json!([{"id":1,"name":"John"}])));
+
+	let request = Request::builder()
+		.uri("/users?name=John&tags=a%2Cb&tags=c")
+		.header(CHECKSUM_HEADER, SCHEMA_CHECKSUM)
+		.body(Body::empty())
+		.unwrap();
+	let users = json!([{"id":1,"name":"John"},{"id":2,"name":"a,b"},{"id":2,"name":"c"}]);
+	assert_eq!(send(request).await,(StatusCode::OK,users));
+}
+
+#[tokio::test]
+async fn router_no_content(){
+	let request = Request::builder()
+		.method("DELETE")
+		.uri("/users/5")
+		.header(CHECKSUM_HEADER, SCHEMA_CHECKSUM)
+		.body(Body::empty())
+		.unwrap();
+
+	assert_eq!(send(request).await.0,StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn router_declared_errors(){
+	let request = Request::builder()
+		.uri("/users/0")
+		.header(CHECKSUM_HEADER, SCHEMA_CHECKSUM)
+		.body(Body::empty())
+		.unwrap();
+
+	assert_eq!(
+		send(request).await,
+		(StatusCode::NOT_FOUND,json!({"message":"user 0 does not exist"}))
+	);
+}
+
+#[tokio::test]
+async fn router_applies_validation_layer(){
+	let request = Request::builder()
+		.uri("/users/5")
+		.body(Body::empty())
+		.unwrap();
+
+	assert_eq!(send(request).await.0,StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn router_validates_request_bodies(){
+	let layer = validation_layer().validate_requests(schema());
+	let request = Request::builder()
+		.method("POST")
+		.uri("/users")
+		.header(CHECKSUM_HEADER, SCHEMA_CHECKSUM)
+		.header("Content-Type", "application/json")
+		.body(Body::from(r#"{"name":5}"#))
+		.unwrap();
+
+	let response = router_with_layer(Server, layer)
+		.oneshot(request)
+		.await
+		.unwrap();
+	assert_eq!(response.status(),StatusCode::UNPROCESSABLE_ENTITY);
+
+	let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+	let rejection = compiler::Rejection::parse(std::str::from_utf8(&body).unwrap()).unwrap();
+	assert_eq!(rejection.code,compiler::RejectionCode::InvalidBody);
+	assert_eq!(rejection.errors[0].path,"$.name");
+	assert_eq!(rejection.errors[0].message,"expected string, found int");
+}
+
+#[test]
+fn embedded_schema(){
+	let (schema,_) = core_types::ApiSchema::load("tests/schemas/server.yml").unwrap();
+
+	assert_eq!(self::schema(),schema);
+}
+
+#[test]
+fn struct_derives(){
+	let user = User { id: 1, name: String::from("Jane") };
+	let mut users = std::collections::HashSet::new();
+	users.insert(user.clone());
+
+	assert!(users.contains(&user));
+	assert!(format!("{:?}",user).contains(r#"name: "Jane""#));
+}
+
+#[test]
+fn struct_attributes(){
+	let profile = Profile { display_name: String::from("Jane") };
+
+	assert_eq!(serde_json::to_value(&profile).unwrap(),json!({"display_name":"Jane"}));
+	assert!(serde_json::from_value::<Profile>(json!({"display_name":"Jane","age":24})).is_err());
+}
+
+#[test]
+fn schema_checksum(){
+	let layer_checksum = core_types::ApiSchema::load("tests/schemas/server.yml").unwrap().0.checksum();
+
+	assert_eq!(SCHEMA_CHECKSUM,layer_checksum);
+	assert!(compiler::ValidationLayer::from_schema("tests/schemas/server.yml").is_ok());
+	assert!(compiler::ValidationLayer::from_schema("tests/schemas/missing.yml").is_err());
+}
@@ -1,38 +1,43 @@
-use compiler::ts::MethodBuilder;
-use core_types::{Endpoint, HttpMethod, SchemaType};
-use quote::quote;
+use compiler::ts::{EndpointOptions, MethodBuilder, TsType};
+use core_types::{Endpoint, HttpMethod, IndexMap, SchemaType};
+use indoc::indoc;
 
 #[test]
 fn get_method_code_gen(){
 	let endpoint = Endpoint{
 		uri: "https://youtube.com/user".to_owned(),
 		method: HttpMethod::Get,
-		input: SchemaType::Boolean,
-		returns: SchemaType::String
+		params: IndexMap::new(),
+		query: IndexMap::new(),
+		input: None,
+		returns: Some(SchemaType::String),
+		errors: IndexMap::new()
 	};
 
 	let method = MethodBuilder::from_endpoint("get_user", &endpoint);
 
-	let body = quote! {
-		try{
-			const response = await fetch("https://youtube.com/user",{
-				headers:{
-					"Api-Checksum": this.checksum
-				}
+	let output = indoc! {r#"
+		async getUser(): Promise<string> {
+			const url = `${this.baseUrl ?? "https://youtube.com"}/user`;
+			const response = await fetch(url, {
+				headers: {
+					...this.headers,
+					[CHECKSUM_HEADER]: this.checksum,
+					[VERSION_HEADER]: SCHEMA_VERSION,
+				},
 			});
-			if (response.ok){
-				const body: string = await response.json();
-				return body;
-			}else{
-				const error = await response.json();
-				throw error;
+			if (response.ok) {
+				return checkValue(validateString, await response.json());
 			}
-		} catch (err){
-			throw err;
-		}
-	}.to_string();
+			const error = await response.json();
+			if (isRejection(error)) {
+				throw new RejectionError(error);
+			}
+			throw error;
+		}"#
+	};
 
-	assert_eq!(method.body(),&body);
+	assert_eq!(method.to_string(),output);
 }
 
 #[test]
@@ -40,32 +45,433 @@ fn post_method_code_gen(){
 	let endpoint = Endpoint{
 		uri: "https://example.com/user".to_owned(),
 		method: HttpMethod::Post,
-		input: SchemaType::Struct("UserPayload".to_string()),
-		returns: SchemaType::Struct("User".to_string())
+		params: IndexMap::new(),
+		query: IndexMap::new(),
+		input: Some(SchemaType::Struct("UserPayload".to_string())),
+		returns: Some(SchemaType::Struct("User".to_string())),
+		errors: IndexMap::new()
 	};
 
 	let method = MethodBuilder::from_endpoint("get_user", &endpoint);
 
-	let body = quote! {
-		try{
-			const response = await fetch("https://example.com/user",{
-				headers:{
-					"Api-Checksum": this.checksum
+	let output = indoc! {r#"
+		async getUser(payload: UserPayload): Promise<User> {
+			const url = `${this.baseUrl ?? "https://example.com"}/user`;
+			const response = await fetch(url, {
+				headers: {
+					...this.headers,
+					[CHECKSUM_HEADER]: this.checksum,
+					[VERSION_HEADER]: SCHEMA_VERSION,
 				},
 				method: "POST",
-				body: JSON.stringify(payload)
+				body: JSON.stringify(encodeUserPayload(payload)),
+			});
+			if (response.ok) {
+				const body = checkValue(validateUser, await response.json());
+				return decodeUser(body);
+			}
+			const error = await response.json();
+			if (isRejection(error)) {
+				throw new RejectionError(error);
+			}
+			throw error;
+		}"#
+	};
+
+	assert_eq!(method.to_string(),output);
+}
+
+#[test]
+fn path_params_code_gen(){
+	let endpoint = Endpoint{
+		uri: "https://example.com/users/{id}/orders/{order_id}".to_owned(),
+		method: HttpMethod::Post,
+		params: IndexMap::from([
+			("id".to_string(),SchemaType::Int),
+			("order_id".to_string(),SchemaType::String),
+		]),
+		query: IndexMap::new(),
+		input: Some(SchemaType::Struct("OrderPayload".to_string())),
+		returns: Some(SchemaType::Struct("Order".to_string())),
+		errors: IndexMap::new()
+	};
+
+	let method = MethodBuilder::from_endpoint("update_order", &endpoint);
+	let parameters: Vec<String> = method.parameters()
+		.iter()
+		.map(|param|param.to_string())
+		.collect();
+
+	assert_eq!(parameters,vec!["id: number","orderId: string","payload: OrderPayload"]);
+
+	let url = concat!(
+		"const url = `${this.baseUrl ?? \"https://example.com\"}/users/${encodeURIComponent(String(id))}",
+		"/orders/${encodeURIComponent(String(orderId))}`;"
+	);
+	assert!(method.to_string().contains(url));
+}
+
+#[test]
+fn query_params_code_gen(){
+	let endpoint = Endpoint{
+		uri: "https://example.com/orders".to_owned(),
+		method: HttpMethod::Get,
+		params: IndexMap::new(),
+		query: IndexMap::from([
+			("tags".to_string(),SchemaType::Array(Box::new(SchemaType::String))),
+		]),
+		input: None,
+		returns: Some(SchemaType::String),
+		errors: IndexMap::new()
+	};
+
+	let method = MethodBuilder::from_endpoint("list_orders", &endpoint);
+	let parameters: Vec<String> = method.parameters()
+		.iter()
+		.map(|param|param.to_string())
+		.collect();
+	assert_eq!(parameters,vec!["query: ListOrdersQuery"]);
+
+	let output = indoc! {r#"
+		async listOrders(query: ListOrdersQuery): Promise<string> {
+			const params = new URLSearchParams();
+			query.tags.forEach((item: string) => params.append("tags", String(item)));
+			const search = params.toString();
+			const url = `${this.baseUrl ?? "https://example.com"}/orders${search ? "?" + search : ""}`;
+			const response = await fetch(url, {
+				headers: {
+					...this.headers,
+					[CHECKSUM_HEADER]: this.checksum,
+					[VERSION_HEADER]: SCHEMA_VERSION,
+				},
+			});
+			if (response.ok) {
+				return checkValue(validateString, await response.json());
+			}
+			const error = await response.json();
+			if (isRejection(error)) {
+				throw new RejectionError(error);
+			}
+			throw error;
+		}"#
+	};
+
+	assert_eq!(method.to_string(),output);
+}
+
+#[test]
+fn no_content_code_gen(){
+	let endpoint = Endpoint{
+		uri: "https://example.com/session".to_owned(),
+		method: HttpMethod::Delete,
+		params: IndexMap::new(),
+		query: IndexMap::new(),
+		input: None,
+		returns: None,
+		errors: IndexMap::new()
+	};
+
+	let method = MethodBuilder::from_endpoint("logout", &endpoint);
+	assert!(method.parameters().is_empty());
+	assert_eq!(method.returns(),&Some(TsType::Void));
+
+	let output = indoc! {r#"
+		async logout(): Promise<void> {
+			const url = `${this.baseUrl ?? "https://example.com"}/session`;
+			const response = await fetch(url, {
+				headers: {
+					...this.headers,
+					[CHECKSUM_HEADER]: this.checksum,
+					[VERSION_HEADER]: SCHEMA_VERSION,
+				},
+				method: "DELETE",
 			});
-			if (response.ok){
-				const body: User = await response.json();
-				return body;
-			}else{
-				const error = await response.json();
-				throw error;
+			if (response.ok) {
+				return;
+			}
+			const error = await response.json();
+			if (isRejection(error)) {
+				throw new RejectionError(error);
 			}
-		} catch (err){
-			throw err;
+			throw error;
+		}"#
+	};
+
+	assert_eq!(method.to_string(),output);
+}
+
+#[test]
+fn head_method_code_gen(){
+	let endpoint = Endpoint{
+		uri: "https://example.com/health".to_owned(),
+		method: HttpMethod::Head,
+		params: IndexMap::new(),
+		query: IndexMap::new(),
+		input: None,
+		returns: None,
+		errors: IndexMap::new()
+	};
+
+	let method = MethodBuilder::from_endpoint("health", &endpoint);
+	assert_eq!(method.returns(),&Some(TsType::Void));
+	let method = method.to_string();
+	assert!(method.contains("\t\tmethod: \"HEAD\",\n"));
+	assert!(method.contains("\tthrow new Error(response.statusText);\n"));
+	assert!(!method.contains("json"));
+}
+
+#[test]
+fn put_method_code_gen(){
+	let endpoint = Endpoint{
+		uri: "https://example.com/user".to_owned(),
+		method: HttpMethod::Put,
+		params: IndexMap::new(),
+		query: IndexMap::new(),
+		input: Some(SchemaType::Struct("User".to_string())),
+		returns: Some(SchemaType::Struct("User".to_string())),
+		errors: IndexMap::new()
+	};
+
+	let method = MethodBuilder::from_endpoint("upsert_user", &endpoint);
+	let options = "\t\tmethod: \"PUT\",\n\t\tbody: JSON.stringify(encodeUser(payload)),\n";
+	assert!(method.to_string().contains(options));
+}
+
+#[test]
+fn relative_uri_code_gen(){
+	let endpoint = Endpoint{
+		uri: "/orders/{id}".to_owned(),
+		method: HttpMethod::Get,
+		params: IndexMap::from([("id".to_string(),SchemaType::Int)]),
+		query: IndexMap::new(),
+		input: None,
+		returns: None,
+		errors: IndexMap::new()
+	};
+
+	let method = MethodBuilder::from_endpoint("get_order", &endpoint).to_string();
+	assert!(method.contains("const url = `${this.baseUrl ?? \"\"}/orders/${encodeURIComponent(String(id))}`;"));
+
+	let method = MethodBuilder::from_endpoint_with_base_url("get_order", &endpoint).to_string();
+	assert!(method.contains("const url = `${this.baseUrl}/orders/${encodeURIComponent(String(id))}`;"));
+}
+
+#[test]
+fn request_validation_code_gen(){
+	let endpoint = Endpoint{
+		uri: "https://example.com/user".to_owned(),
+		method: HttpMethod::Post,
+		params: IndexMap::new(),
+		query: IndexMap::new(),
+		input: Some(SchemaType::Struct("UserPayload".to_string())),
+		returns: None,
+		errors: IndexMap::new()
+	};
+
+	let method = MethodBuilder::from_endpoint("create_user", &endpoint);
+	assert!(method.to_string().contains("\t\tbody: JSON.stringify(encodeUserPayload(payload)),\n"));
+
+	let options = EndpointOptions{validate_requests: true, ..Default::default()};
+	let method = MethodBuilder::from_endpoint_with_options("create_user", &endpoint, options);
+	let body = indoc! {"
+		\t\tbody: JSON.stringify(
+		\t\t\tcheckValue(validateUserPayload, encodeUserPayload(payload)),
+		\t\t),
+	"};
+	assert!(method.to_string().contains(body));
+}
+
+#[test]
+fn codegen_rejects_invalid_schema(){
+	let dir = std::env::temp_dir().join("codegen_rejects_invalid_schema");
+	std::fs::create_dir_all(&dir).unwrap();
+	let schema_path = dir.join("schema.yml");
+	let output_path = dir.join("types.ts");
+
+	std::fs::write(&schema_path, concat!(
+		"version: 0.1.0\n",
+		"schema_diff: none\n",
+		"structs:\n",
+		"  User:\n",
+		"    manager: Usr\n",
+		"endpoints:\n",
+		"  get_user:\n",
+		"    uri: https://example.com/user\n",
+		"    method: GET\n",
+		"    returns: Usr\n",
+	)).unwrap();
+
+	let result = compiler::ts::codegen(
+		schema_path.to_str().unwrap(),
+		output_path.to_str().unwrap()
+	);
+
+	let Err(compiler::Error::Validation(error)) = result else {
+		panic!("Expected a validation error");
+	};
+	assert_eq!(error.diagnostics.len(),2);
+	assert!(!output_path.exists());
+}
+
+#[test]
+fn codegen_embeds_checksum(){
+	let directory = std::env::temp_dir().join("codegen_embeds_checksum");
+	std::fs::create_dir_all(&directory).unwrap();
+	let output_path = directory.join("types.ts");
+
+	compiler::ts::codegen("tests/schemas/server.yml", output_path.to_str().unwrap()).unwrap();
+
+	let checksum = core_types::ApiSchema::load("tests/schemas/server.yml").unwrap().0.checksum();
+	let contents = std::fs::read_to_string(output_path).unwrap();
+	assert!(contents.contains(&format!("export const SCHEMA_CHECKSUM = \"{}\";",checksum)));
+	assert!(contents.contains("export interface ClientOptions {"));
+	assert!(contents.contains("\tbaseUrl?: string;\n\theaders: Record<string, string>;\n"));
+	assert!(contents.contains("\t\tthis.baseUrl = options?.baseUrl;\n"));
+	assert!(contents.contains("\t\tthis.checksum = SCHEMA_CHECKSUM;\n"));
+}
+
+#[test]
+fn codegen_rejection_types(){
+	let directory = std::env::temp_dir().join("codegen_rejection_types");
+	std::fs::create_dir_all(&directory).unwrap();
+	let output_path = directory.join("types.ts");
+
+	compiler::ts::codegen("tests/schemas/server.yml", output_path.to_str().unwrap()).unwrap();
+
+	let contents = std::fs::read_to_string(output_path).unwrap();
+	assert!(contents.contains(indoc! {r#"
+		export interface Rejection {
+			code:
+				| "missing_checksum"
+				| "invalid_checksum"
+				| "incompatible_schema"
+				| "invalid_body"
+				| "body_too_large"
+				| "invalid_response";
+			message: string;
+	"#}));
+	assert!(contents.contains(indoc! {r#"
+		/**
+		 * Thrown when the server rejects a request, the `code` says why. An
+		 * `invalid_response` code is a bug in the server, not in the client.
+		 */
+		export class RejectionError extends Error {"#
+	}));
+	assert!(contents.contains("function isRejection(value: any): value is Rejection {"));
+}
+
+#[test]
+fn codegen_declared_errors(){
+	let directory = std::env::temp_dir().join("codegen_declared_errors");
+	std::fs::create_dir_all(&directory).unwrap();
+	let output_path = directory.join("types.ts");
+
+	compiler::ts::codegen("tests/schemas/server.yml", output_path.to_str().unwrap()).unwrap();
+
+	let contents = std::fs::read_to_string(output_path).unwrap();
+	assert!(contents.contains("export type GetUserError = { status: 404; body: NotFound };"));
+	assert!(contents.contains("export class ApiError<\n\tE extends { status: number; body: unknown },\n> extends Error {"));
+
+	let throws = indoc! {r#"
+		if (response.status === 404) {
+			throw new ApiError<GetUserError>({
+				status: 404,
+				body: decodeNotFound(checkValue(validateNotFound, error)),
+			});
 		}
-	}.to_string();
+	"#};
+	let throws: String = throws.lines().map(|line|format!("\t\t{line}\n")).collect();
+	assert!(contents.contains(&throws));
+}
+
+#[test]
+fn codegen_naming_conventions(){
+	let directory = std::env::temp_dir().join("codegen_naming_conventions");
+	std::fs::create_dir_all(&directory).unwrap();
+	let schema_path = directory.join("schema.yml");
+	let output_path = directory.join("types.ts");
+
+	std::fs::write(&schema_path, concat!(
+		"version: 0.1.0\n",
+		"schema_diff: none\n",
+		"structs:\n",
+		"  user_profile:\n",
+		"    created_at: string\n",
+		"endpoints:\n",
+		"  get_profile:\n",
+		"    uri: https://example.com/profiles/{profile_id}\n",
+		"    method: GET\n",
+		"    params:\n",
+		"      profile_id: int\n",
+		"    returns: user_profile\n",
+	)).unwrap();
+
+	compiler::ts::codegen(schema_path.to_str().unwrap(), output_path.to_str().unwrap()).unwrap();
 
-	assert_eq!(method.body(),&body);
-}
\ No newline at end of file
+	let contents = std::fs::read_to_string(output_path).unwrap();
+	assert!(contents.contains("export interface UserProfile {\n\tcreatedAt: string;\n}"));
+	assert!(contents.contains("export function decodeUserProfile(value: any): UserProfile {\n\treturn {\n\t\tcreatedAt: value.created_at,\n"));
+	assert!(contents.contains("export function encodeUserProfile(value: UserProfile): any {\n\treturn {\n\t\tcreated_at: value.createdAt,\n"));
+	assert!(contents.contains("async getProfile(profileId: number): Promise<UserProfile> {"));
+	assert!(contents.contains("${encodeURIComponent(String(profileId))}"));
+	assert!(contents.contains("return decodeUserProfile(body);"));
+}
+
+#[test]
+fn codegen_declaration_order(){
+	let directory = std::env::temp_dir().join("codegen_declaration_order");
+	std::fs::create_dir_all(&directory).unwrap();
+	let first_path = directory.join("first.ts");
+	let second_path = directory.join("second.ts");
+
+	compiler::ts::codegen("tests/schemas/server.yml", first_path.to_str().unwrap()).unwrap();
+	compiler::ts::codegen("tests/schemas/server.yml", second_path.to_str().unwrap()).unwrap();
+
+	let first = std::fs::read_to_string(first_path).unwrap();
+	assert_eq!(first,std::fs::read_to_string(second_path).unwrap());
+
+	let positions: Vec<usize> = [
+		"export interface User {\n\tid: number;\n\tname: string;\n}",
+		"export interface UserPayload {",
+		"export interface Profile {",
+		"export interface NotFound {",
+		"async createUser(",
+		"async getUser(",
+		"async listUsers(",
+		"async deleteUser(",
+	].iter().map(|item|first.find(item).unwrap()).collect();
+	assert!(positions.is_sorted());
+}
+
+#[test]
+fn codegen_validators(){
+	let directory = std::env::temp_dir().join("codegen_validators");
+	std::fs::create_dir_all(&directory).unwrap();
+	let output_path = directory.join("types.ts");
+
+	compiler::ts::codegen("tests/schemas/server.yml", output_path.to_str().unwrap()).unwrap();
+
+	let contents = std::fs::read_to_string(output_path).unwrap();
+	assert!(contents.contains("export class ValidationError extends Error {"));
+	assert!(contents.contains("export function checkValue(validate: Validator, value: any): any {"));
+	assert!(contents.contains(indoc! {r#"
+		export function validateProfile(
+			value: any,
+			path: string,
+			errors: ValidationIssue[],
+		): void {
+			if (kind(value) !== "object") {
+				errors.push(mismatch("Profile", value, path));
+				return;
+			}
+			validateString(value.display_name, `${path}.display_name`, errors);
+		}
+	"#}));
+	assert!(contents.contains(indoc! {r#"
+		const body = checkValue(
+			validateArray(validateUser),
+			await response.json(),
+		);
+		return body.map((item0: any) => decodeUser(item0));
+	"#}.lines().map(|line|format!("\t\t\t{line}\n")).collect::<String>().as_str()));
+}
@@ -0,0 +1,134 @@
+//! A stable checksum of the schema, shared by the server and clients.
+use serde_yaml::Value;
+use crate::ApiSchema;
+
+impl ApiSchema{
+	/// The SHA-256 checksum of the schema as a hex string.
+	///
+	/// The checksum is computed over a canonical form of the schema, so
+	/// it does not depend on the order of keys, whitespace, comments or
+	/// which files the schema was split into. The `codegen` section is 
+	/// left out, it doesn't change what's sent over the wire.
+	///
+	/// ```
+	/// use core_types::ApiSchema;
+	///
+	/// let a = ApiSchema::parse("version: 0.1.0\nschema_diff: none\nstructs: {}\nendpoints: {}").unwrap();
+	/// let b = ApiSchema::parse("endpoints: {}\nstructs: {}\nschema_diff: none\nversion: 0.1.0").unwrap();
+	/// assert_eq!(a.checksum(),b.checksum());
+	/// ```
+	pub fn checksum(&self) -> String{
+		let mut value = serde_yaml::to_value(self)
+			.expect("Schemas should always be serializable");
+		if let Value::Mapping(mapping) = &mut value{
+			mapping.remove("codegen");
+		}
+		let mut canonical = String::new();
+		write_canonical(&value, &mut canonical);
+		sha256::digest(canonical)
+	}
+}
+
+/// Write the value as compact json-like text with the keys of every
+/// mapping sorted.
+fn write_canonical(value: &Value, out: &mut String){
+	match value {
+		Value::Null => out.push_str("null"),
+		Value::Bool(value) => out.push_str(&value.to_string()),
+		Value::Number(value) => out.push_str(&value.to_string()),
+		Value::String(value) => out.push_str(&format!("{:?}",value)),
+		Value::Sequence(values) => {
+			out.push('[');
+			for (i,value) in values.iter().enumerate(){
+				if i > 0{
+					out.push(',');
+				}
+				write_canonical(value, out);
+			}
+			out.push(']');
+		},
+		Value::Mapping(mapping) => {
+			let mut entries: Vec<(String,&Value)> = mapping.iter()
+				.map(|(key,value)|{
+					let mut canonical_key = String::new();
+					write_canonical(key, &mut canonical_key);
+					(canonical_key,value)
+				})
+				.collect();
+			entries.sort_by(|(a,_),(b,_)|a.cmp(b));
+
+			out.push('{');
+			for (i,(key,value)) in entries.into_iter().enumerate(){
+				if i > 0{
+					out.push(',');
+				}
+				out.push_str(&key);
+				out.push(':');
+				write_canonical(value, out);
+			}
+			out.push('}');
+		},
+		Value::Tagged(tagged) => {
+			out.push_str(&format!("!{} ",tagged.tag));
+			write_canonical(&tagged.value, out);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	#[test]
+	fn checksum_ignores_formatting(){
+		let a = ApiSchema::parse(concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    id: int\n",
+			"    name: string\n",
+			"endpoints: {}\n",
+		)).unwrap();
+		let b = ApiSchema::parse(concat!(
+			"# Users\n",
+			"version:   0.1.0\n",
+			"schema_diff: none\n",
+			"endpoints: {}\n",
+			"structs: { User: { name: string, id: int } }\n",
+		)).unwrap();
+
+		assert_eq!(a.checksum(),b.checksum());
+		assert_eq!(a.checksum().len(),64);
+	}
+
+	#[test]
+	fn checksum_changes_with_schema(){
+		let a = ApiSchema::parse("version: 0.1.0\nschema_diff: none\nstructs:\n  User:\n    id: int\nendpoints: {}\n").unwrap();
+		let b = ApiSchema::parse("version: 0.1.0\nschema_diff: none\nstructs:\n  User:\n    id: string\nendpoints: {}\n").unwrap();
+
+		assert_ne!(a.checksum(),b.checksum());
+	}
+
+	#[test]
+	fn checksum_ignores_codegen(){
+		let a = ApiSchema::parse("version: 0.1.0\nschema_diff: none\nstructs:\n  User:\n    id: int\nendpoints: {}\n").unwrap();
+		let b = ApiSchema::parse(concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    id: int\n",
+			"endpoints: {}\n",
+			"codegen:\n",
+			"  client_name: UserClient\n",
+			"  base_url: https://example.com\n",
+			"  typescript:\n",
+			"    output: client.ts\n",
+			"  rust:\n",
+			"    derives: [Hash]\n",
+		)).unwrap();
+
+		assert_eq!(a.checksum(),b.checksum());
+	}
+}
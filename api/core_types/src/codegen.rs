@@ -0,0 +1,237 @@
+//! Options for the generated code.
+//!
+//! ```yaml
+//! codegen:
+//!   # The name of the generated clients, `Client` by default
+//!   client_name: ShopClient
+//!   # Clients send requests here instead of the host in each endpoint's uri
+//!   base_url: https://api.example.com/v1
+//!   typescript:
+//!     # Relative to the schema file
+//!     output: ../typescript-client/types.ts
+//!     # Check payloads before they're sent, responses are always checked
+//!     validate_requests: true
+//!   rust:
+//!     client_name: ShopApi
+//!     # Added to every generated struct and enum
+//!     derives: [Eq]
+//!     # Added to every generated struct
+//!     attributes:
+//!       - serde(deny_unknown_fields)
+//!     structs:
+//!       User:
+//!         derives: [Hash]
+//!         attributes:
+//!           - doc(hidden)
+//! ```
+use serde::{Deserialize, Serialize};
+use crate::IndexMap;
+
+/// The `codegen` section of the schema.
+#[derive(Debug, Serialize, Deserialize,Clone,PartialEq,Default)]
+#[serde(deny_unknown_fields)]
+pub struct CodegenConfig{
+	/// The name of the generated clients in every language.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub client_name: Option<String>,
+	/// The url that the clients send requests to by default, the paths
+	/// of the endpoints are appended to it.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub base_url: Option<String>,
+	#[serde(default, skip_serializing_if = "RustConfig::is_empty")]
+	pub rust: RustConfig,
+	#[serde(default, skip_serializing_if = "TypescriptConfig::is_empty")]
+	pub typescript: TypescriptConfig
+}
+
+impl CodegenConfig{
+	/// The name of the clients when none is set.
+	pub const DEFAULT_CLIENT_NAME: &str = "Client";
+
+	pub fn is_empty(&self) -> bool{
+		self.client_name.is_none()
+			&& self.base_url.is_none()
+			&& self.rust.is_empty()
+			&& self.typescript.is_empty()
+	}
+
+	/// The name of the rust client, the language specific name first.
+	pub fn rust_client_name(&self) -> &str{
+		self.rust.client_name.as_deref()
+			.or(self.client_name.as_deref())
+			.unwrap_or(Self::DEFAULT_CLIENT_NAME)
+	}
+
+	/// The name of the typescript client, the language specific name first.
+	pub fn typescript_client_name(&self) -> &str{
+		self.typescript.client_name.as_deref()
+			.or(self.client_name.as_deref())
+			.unwrap_or(Self::DEFAULT_CLIENT_NAME)
+	}
+}
+
+/// Options for the code generated by `compiler::ts`.
+#[derive(Debug, Serialize, Deserialize,Clone,PartialEq,Default)]
+#[serde(deny_unknown_fields)]
+pub struct TypescriptConfig{
+	/// Overrides the shared `client_name`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub client_name: Option<String>,
+	/// The file the code is written to, relative to the schema file.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub output: Option<String>,
+	/// Check request payloads against the schema before sending them.
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub validate_requests: bool
+}
+
+impl TypescriptConfig{
+	pub fn is_empty(&self) -> bool{
+		self.client_name.is_none() && self.output.is_none() && !self.validate_requests
+	}
+}
+
+/// Options for the code generated by `code_gen!`.
+#[derive(Debug, Serialize, Deserialize,Clone,PartialEq,Default)]
+#[serde(deny_unknown_fields)]
+pub struct RustConfig{
+	/// Overrides the shared `client_name`.
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub client_name: Option<String>,
+	/// Extra derives for every generated struct and enum, e.g. `Hash`.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub derives: Vec<String>,
+	/// Extra attributes for every generated struct, without the `#[]`,
+	/// e.g. `serde(deny_unknown_fields)`.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub attributes: Vec<String>,
+	/// Options for individual structs
+	#[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+	pub structs: IndexMap<String,RustStructConfig>
+}
+
+impl RustConfig{
+	/// The derives that every generated struct and enum has.
+	pub const DEFAULT_DERIVES: &[&str] = &[
+		"::serde::Serialize",
+		"::serde::Deserialize",
+		"Debug",
+		"Clone",
+		"PartialEq"
+	];
+
+	pub fn is_empty(&self) -> bool{
+		self.client_name.is_none()
+			&& self.derives.is_empty()
+			&& self.attributes.is_empty()
+			&& self.structs.is_empty()
+	}
+
+	/// The extra derives for the struct, the global derives first.
+	///
+	/// Derives that are already in [`RustConfig::DEFAULT_DERIVES`] or
+	/// listed more than once are skipped.
+	pub fn struct_derives(&self, name: &str) -> Vec<&str>{
+		let local = self.structs.get(name)
+			.map(|config|config.derives.as_slice())
+			.unwrap_or_default();
+
+		extra_derives(self.derives.iter().chain(local))
+	}
+
+	/// The extra derives for every enum.
+	pub fn enum_derives(&self) -> Vec<&str>{
+		extra_derives(&self.derives)
+	}
+
+	/// The extra attributes for the struct, the global attributes first.
+	pub fn struct_attributes(&self, name: &str) -> Vec<&str>{
+		let local = self.structs.get(name)
+			.map(|config|config.attributes.as_slice())
+			.unwrap_or_default();
+
+		self.attributes.iter()
+			.chain(local)
+			.map(String::as_str)
+			.collect()
+	}
+}
+
+/// Options for a single generated struct.
+#[derive(Debug, Serialize, Deserialize,Clone,PartialEq,Default)]
+#[serde(deny_unknown_fields)]
+pub struct RustStructConfig{
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub derives: Vec<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub attributes: Vec<String>
+}
+
+fn extra_derives<'a>(all: impl IntoIterator<Item = &'a String>) -> Vec<&'a str>{
+	let mut derives: Vec<&str> = vec![];
+	for derive in all{
+		let derive = derive.as_str();
+		let is_default = RustConfig::DEFAULT_DERIVES.iter()
+			.any(|default|derive_name(default) == derive_name(derive));
+		let is_duplicate = derives.iter()
+			.any(|existing|derive_name(existing) == derive_name(derive));
+		if !is_default && !is_duplicate{
+			derives.push(derive);
+		}
+	}
+	derives
+}
+
+/// The name of the derive without its path, `serde::Serialize` and
+/// `Serialize` are the same derive.
+fn derive_name(derive: &str) -> &str{
+	derive.rsplit("::").next().unwrap_or(derive).trim()
+}
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	#[test]
+	fn struct_derives(){
+		let config: RustConfig = serde_yaml::from_str(concat!(
+			"derives: [Eq, serde::Serialize]\n",
+			"structs:\n",
+			"  User:\n",
+			"    derives: [Hash, Eq]\n",
+		)).unwrap();
+
+		assert_eq!(config.struct_derives("User"),vec!["Eq","Hash"]);
+		assert_eq!(config.struct_derives("Order"),vec!["Eq"]);
+		assert_eq!(config.enum_derives(),vec!["Eq"]);
+	}
+
+	#[test]
+	fn struct_attributes(){
+		let config: RustConfig = serde_yaml::from_str(concat!(
+			"attributes: [serde(deny_unknown_fields)]\n",
+			"structs:\n",
+			"  User:\n",
+			"    attributes: ['doc(hidden)']\n",
+		)).unwrap();
+
+		assert_eq!(
+			config.struct_attributes("User"),
+			vec!["serde(deny_unknown_fields)","doc(hidden)"]
+		);
+		assert_eq!(config.struct_attributes("Order"),vec!["serde(deny_unknown_fields)"]);
+	}
+
+	#[test]
+	fn client_names(){
+		let config: CodegenConfig = serde_yaml::from_str(concat!(
+			"client_name: ShopClient\n",
+			"rust:\n",
+			"  client_name: ShopApi\n",
+		)).unwrap();
+
+		assert_eq!(config.rust_client_name(),"ShopApi");
+		assert_eq!(config.typescript_client_name(),"ShopClient");
+		assert_eq!(CodegenConfig::default().typescript_client_name(),"Client");
+	}
+}
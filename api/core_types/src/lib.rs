@@ -1,8 +1,26 @@
-use proc_macro2::Span;
+pub mod validate;
+pub mod naming;
+pub mod codegen;
+pub mod value;
+mod load;
+mod checksum;
+mod version;
+mod rejection;
+pub use load::{LoadError, SchemaFile};
+pub use codegen::{CodegenConfig, RustConfig, TypescriptConfig};
+pub use version::{Version, ParseVersionError, SchemaDiff, VERSION_HEADER};
+pub use rejection::{Rejection, RejectionCode};
+pub use value::FieldError;
+/// The map used for every section of the schema, it keeps the order the
+/// items were declared in so the generated code is the same on every run.
+pub use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use quote::{quote, ToTokens, TokenStreamExt};
 
+/// The header that clients send the schema checksum in, unless the 
+/// schema sets its own `checksum_header`.
+pub const CHECKSUM_HEADER: &str = "Api-Schema-Checksum";
+
 /// The api schema
 /// 
 /// # Example schema
@@ -12,63 +30,572 @@ use quote::{quote, ToTokens, TokenStreamExt};
 /// 
 /// structs:
 ///   User:
-///     id: string,
-///     email: string,
+///     id: string
+///     email: string
 ///     phone_number: string
+/// 
+/// enums:
+///   Status:
+///     - active
+///     - suspended
 /// ```
 #[derive(Debug, Serialize, Deserialize,Clone,PartialEq,Default)]
 #[serde(deny_unknown_fields)]
 pub struct ApiSchema {
-    pub version: String,
-    pub schema_diff: String,
-    pub structs: HashMap<String, HashMap<String, SchemaType>>,
-    pub endpoints: HashMap<String, Endpoint>,
+    pub version: Version,
+	/// The largest version difference between a client and the server 
+	/// that the server accepts
+    pub schema_diff: SchemaDiff,
+	/// The header that the checksum is sent in, defaults to [`CHECKSUM_HEADER`]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub checksum_header: Option<String>,
+	/// The header that the version is sent in, defaults to [`VERSION_HEADER`]
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub version_header: Option<String>,
+	/// Other schema files to merge into this one, relative to this file
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub include: Vec<String>,
+    pub structs: IndexMap<String, IndexMap<String, SchemaType>>,
+	#[serde(default)]
+	pub enums: IndexMap<String, Enum>,
+    pub endpoints: IndexMap<String, Endpoint>,
+	/// Options for the generated code
+	#[serde(default, skip_serializing_if = "CodegenConfig::is_empty")]
+	pub codegen: CodegenConfig,
 }
 
 impl ApiSchema{
 	pub fn parse(contents: &str) -> Result<Self,serde_yaml::Error>{
+		// Deserializing into a map silently keeps the last duplicate key, 
+		// parsing into a value first rejects them
+		serde_yaml::from_str::<serde_yaml::Value>(contents)?;
 		let schema:Self = serde_yaml::from_str(contents)?;
 		
 		Ok(schema)
 	}
+
+	/// The header that clients send the schema checksum in.
+	pub fn checksum_header(&self) -> &str{
+		self.checksum_header.as_deref().unwrap_or(CHECKSUM_HEADER)
+	}
+
+	/// The header that clients send the schema version in.
+	pub fn version_header(&self) -> &str{
+		self.version_header.as_deref().unwrap_or(VERSION_HEADER)
+	}
+
+	/// Find the endpoint that a request with the method, e.g. `POST`, 
+	/// and path is sent to.
+	/// 
+	/// Like the router, literal segments take precedence over parameters, 
+	/// so `/users/me` is sent to `/users/me` rather than `/users/{id}`.
+	pub fn find_endpoint(&self, method: &str, path: &str) -> Option<(&String,&Endpoint)>{
+		self.endpoints
+			.iter()
+			.filter(|(_,endpoint)|endpoint.method.as_str() == method && endpoint.matches_path(path))
+			.max_by_key(|(_,endpoint)|{
+				endpoint.path()
+					.split('/')
+					.map(|segment|!segment.contains('{'))
+					.collect::<Vec<_>>()
+			})
+	}
 }
 
 /// A url endpoint
+/// 
+/// The uri can contain path parameters in braces, the type of each 
+/// parameter is declared in `params`.
+/// 
+/// ```yaml
+/// endpoints:
+///   get_order:
+///     uri: https://example.com/users/{id}/orders/{order_id}
+///     method: GET
+///     params:
+///       id: int
+///       order_id: string
+///     returns: Order
+///   list_orders:
+///     uri: https://example.com/orders
+///     method: GET
+///     query:
+///       page: int?
+///       tags: string[]
+///     returns: Order[]
+///   delete_order:
+///     uri: https://example.com/orders/{id}
+///     method: DELETE
+///     params:
+///       id: string
+///     errors:
+///       404: NotFound
+///       409: OrderShipped
+/// ```
+/// 
+/// `GET` and `HEAD` requests cannot have a body, so those endpoints 
+/// cannot have an input. `HEAD` responses cannot have a body either.
 #[derive(Debug, Serialize, Deserialize,Clone,PartialEq)]
 pub struct Endpoint {
 	pub uri: String,
     pub method: HttpMethod,
-    pub input: SchemaType, // TODO This should be optional
-    pub returns: SchemaType,
+	/// The types of the path parameters in the uri
+	#[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+	pub params: IndexMap<String, SchemaType>,
+	/// Query string parameters, these can be primitives, optional 
+	/// primitives or lists of primitives
+	#[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+	pub query: IndexMap<String, SchemaType>,
+	/// The request body, endpoints without an input don't send a body
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<SchemaType>,
+	/// The response body, endpoints without a return type respond 
+	/// with no content
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+    pub returns: Option<SchemaType>,
+	/// The body of each error response, by status code
+	#[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+	pub errors: IndexMap<u16, SchemaType>,
+}
+
+impl Endpoint{
+	/// Split the uri into literal text and `{name}` path parameters.
+	pub fn uri_segments(&self) -> Result<Vec<UriSegment<'_>>,String>{
+		UriSegment::parse(&self.uri)
+	}
+
+	/// The path of the uri without the scheme and host, 
+	/// `https://example.com/users/{id}` becomes `/users/{id}`.
+	pub fn path(&self) -> &str{
+		let path = match self.uri.split_once("://") {
+			Some((_,rest)) => rest.find('/').map(|index|&rest[index..]).unwrap_or("/"),
+			None => &self.uri
+		};
+		
+		path.split(['?','#']).next().unwrap_or(path)
+	}
+
+	/// The scheme and host of the uri, `https://example.com/users/{id}`
+	/// becomes `https://example.com`. Relative uris have no origin.
+	pub fn origin(&self) -> Option<&str>{
+		let (scheme,rest) = self.uri.split_once("://")?;
+		let host = rest.find(['/','?','#']).unwrap_or(rest.len());
+		Some(&self.uri[..scheme.len() + 3 + host])
+	}
+
+	/// The declared error responses, ordered by status code.
+	pub fn error_responses(&self) -> Vec<ErrorResponse<'_>>{
+		let mut errors: Vec<_> = self.errors
+			.iter()
+			.map(|(status,_type)|ErrorResponse{status: *status, _type})
+			.collect();
+		errors.sort_by_key(|error|error.status);
+		errors
+	}
+
+	/// The names of the path parameters in the uri, in order of appearance.
+	pub fn path_params(&self) -> Vec<&str>{
+		self.uri_segments()
+			.unwrap_or_default()
+			.into_iter()
+			.filter_map(|segment|match segment {
+				UriSegment::Param(name) => Some(name),
+				UriSegment::Literal(_) => None
+			})
+			.collect()
+	}
+
+	/// Returns `true` if a request path matches the endpoint's path, each 
+	/// path parameter matches one or more characters within a segment.
+	///
+	/// ```
+	/// use core_types::{Endpoint,HttpMethod};
+	///
+	/// let endpoint = Endpoint{
+	/// 	uri: String::from("https://example.com/users/{id}"),
+	/// 	method: HttpMethod::Get,
+	/// 	params: Default::default(),
+	/// 	query: Default::default(),
+	/// 	input: None,
+	/// 	returns: None,
+	/// 	errors: Default::default()
+	/// };
+	/// assert!(endpoint.matches_path("/users/5"));
+	/// assert!(!endpoint.matches_path("/users/5/orders"));
+	/// ```
+	pub fn matches_path(&self, path: &str) -> bool{
+		let Ok(segments) = UriSegment::parse(self.path()) else {
+			return false;
+		};
+		matches_segments(&segments, path)
+	}
+}
+
+fn matches_segments(segments: &[UriSegment], path: &str) -> bool{
+	match segments {
+		[] => path.is_empty(),
+		[UriSegment::Literal(text),rest @ ..] => {
+			path.strip_prefix(text).is_some_and(|path|matches_segments(rest, path))
+		},
+		[UriSegment::Param(_),rest @ ..] => {
+			// Try every length of the parameter up to the end of the segment
+			let end = path.find('/').unwrap_or(path.len());
+			(1..=end).any(|i|path.is_char_boundary(i) && matches_segments(rest, &path[i..]))
+		}
+	}
+}
+
+/// An error response declared by an endpoint.
+#[derive(Debug,Clone,Copy,PartialEq)]
+pub struct ErrorResponse<'a>{
+	pub status: u16,
+	pub _type: &'a SchemaType
 }
 
+impl ErrorResponse<'_>{
+	/// The name of the error in the generated code, the reason phrase of 
+	/// the status in pascal case, e.g. `404` becomes `NotFound`. Statuses 
+	/// without a reason phrase become `Status{code}`.
+	/// 
+	/// ```
+	/// use core_types::{ErrorResponse,SchemaType};
+	/// 
+	/// let error = ErrorResponse{status: 404, _type: &SchemaType::String};
+	/// assert_eq!(error.variant(),"NotFound");
+	/// ```
+	pub fn variant(&self) -> String{
+		let reason = http::StatusCode::from_u16(self.status)
+			.ok()
+			.and_then(|status|status.canonical_reason());
+		match reason {
+			Some(reason) => reason
+				.split(|c: char|!c.is_ascii_alphanumeric())
+				.map(|word|naming::pascal_case(&word.to_ascii_lowercase()))
+				.collect(),
+			None => format!("Status{}",self.status)
+		}
+	}
+}
+
+/// A part of an endpoint uri.
+#[derive(Debug,Clone,Copy,PartialEq, Eq)]
+pub enum UriSegment<'a>{
+	/// Text that is sent as is.
+	Literal(&'a str),
+	/// A path parameter, `{name}`.
+	Param(&'a str)
+}
+
+impl<'a> UriSegment<'a>{
+	/// Split a uri into literal text and `{name}` path parameters.
+	/// 
+	/// # Example
+	/// ```
+	/// use core_types::UriSegment;
+	/// 
+	/// let segments = UriSegment::parse("/users/{id}/orders").unwrap();
+	/// assert_eq!(segments,vec![
+	/// 	UriSegment::Literal("/users/"),
+	/// 	UriSegment::Param("id"),
+	/// 	UriSegment::Literal("/orders"),
+	/// ]);
+	/// ```
+	pub fn parse(uri: &'a str) -> Result<Vec<Self>,String>{
+		let mut segments = vec![];
+		let mut rest = uri;
+
+		while let Some(start) = rest.find(['{','}']){
+			if rest[start..].starts_with('}'){
+				return Err(format!("unmatched `}}` in uri `{}`",uri));
+			}
+
+			let Some(length) = rest[start+1..].find(['{','}']) else {
+				return Err(format!("unclosed `{{` in uri `{}`",uri));
+			};
+			let end = start + 1 + length;
+			if rest[end..].starts_with('{'){
+				return Err(format!("nested `{{` in uri `{}`",uri));
+			}
+
+			if start > 0{
+				segments.push(Self::Literal(&rest[..start]));
+			}
+			segments.push(Self::Param(&rest[start+1..end]));
+			rest = &rest[end+1..];
+		}
+
+		if !rest.is_empty(){
+			segments.push(Self::Literal(rest));
+		}
+
+		Ok(segments)
+	}
+}
+
+/// An enum definition, either a list of string values or a tagged union 
+/// of structs.
+/// 
+/// ```yaml
+/// enums:
+///   Status:
+///     - active
+///     - suspended
+///   Shape:
+///     tag: kind
+///     variants:
+///       Circle:
+///         radius: float
+///       Square:
+///         side: float
+/// ```
 #[derive(Debug, Serialize, Deserialize,Clone,PartialEq)]
-#[serde(rename_all = "lowercase")]
+#[serde(untagged)]
+pub enum Enum{
+	/// A plain enum, serialized as one of its string values.
+	Simple(Vec<String>),
+	/// A data carrying enum, serialized as an object with a tag field.
+	Tagged(TaggedUnion)
+}
+
+/// A data carrying enum, each variant is serialized as an object whose 
+/// `tag` field holds the variant name.
+#[derive(Debug, Serialize, Deserialize,Clone,PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TaggedUnion{
+	/// The name of the field that holds the variant name, defaults to `type`.
+	#[serde(default = "TaggedUnion::default_tag")]
+	pub tag: String,
+	pub variants: IndexMap<String, IndexMap<String, SchemaType>>
+}
+
+impl TaggedUnion{
+	fn default_tag() -> String{
+		String::from("type")
+	}
+}
+
+/// A type in the schema.
+/// 
+/// Types are written as strings in the schema file:
+/// 
+/// ```yaml
+/// structs:
+///   User:
+///     id: int
+///     tags: string[]
+///     nickname: string?
+///     metadata: map<string, int>
+///     friends: User[]?
+/// ```
+#[derive(Debug,Clone,PartialEq)]
 pub enum SchemaType {
     Int,
 	Float,
     String,
 	Boolean,
+	/// A list of values, `T[]`
+	Array(Box<SchemaType>),
+	/// A value that may be missing or null, `T?`
+	Optional(Box<SchemaType>),
+	/// A map with string keys, `map<string, T>`
+	Map(Box<SchemaType>),
 	// Custom struct 
-	#[serde(untagged)]
 	Struct(String)
 }
 
 impl SchemaType{
+	/// Returns `true` if the type is an int, float, string or boolean.
+	pub fn is_primitive(&self) -> bool{
+		matches!(self,Self::Int | Self::Float | Self::String | Self::Boolean)
+	}
+
 	/// Parse into a native rust type
 	pub fn parse(&self) -> proc_macro2::TokenStream{
 		match self {
 			Self::Int => quote!{ i32 },
-			Self::String => quote!{ String },
+			Self::String => quote!{ ::std::string::String },
 			Self::Boolean => quote!{ bool },
 			Self::Float => quote!{ f32 },
+			Self::Array(inner) => {
+				let inner = inner.parse();
+				quote!{ ::std::vec::Vec<#inner> }
+			},
+			Self::Optional(inner) => {
+				let inner = inner.parse();
+				quote!{ ::std::option::Option<#inner> }
+			},
+			Self::Map(inner) => {
+				let inner = inner.parse();
+				quote!{ ::std::collections::HashMap<::std::string::String,#inner> }
+			},
 			Self::Struct(name) => {
-				// TODO test this
-				let ident = syn::Ident::new(name, proc_macro2::Span::call_site());
+				let ident = syn::Ident::new(&naming::pascal_case(name), proc_macro2::Span::call_site());
                 quote! { #ident }
 			},
 		}
 	}
+
+	/// Returns `true` if the type is [`SchemaType::Optional`].
+	pub fn is_optional(&self) -> bool{
+		matches!(self,Self::Optional(_))
+	}
+}
+
+impl std::fmt::Display for SchemaType{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Int => f.write_str("int"),
+			Self::Float => f.write_str("float"),
+			Self::String => f.write_str("string"),
+			Self::Boolean => f.write_str("boolean"),
+			Self::Array(inner) => write!(f,"{}[]",inner),
+			Self::Optional(inner) => write!(f,"{}?",inner),
+			Self::Map(inner) => write!(f,"map<string, {}>",inner),
+			Self::Struct(name) => f.write_str(name),
+		}
+	}
+}
+
+impl std::str::FromStr for SchemaType{
+	type Err = ParseTypeError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let mut parser = TypeParser{source: s, position: 0};
+		let _type = parser.parse_type()?;
+		parser.skip_whitespace();
+		
+		if parser.position != s.len(){
+			return Err(ParseTypeError::new(s, "unexpected trailing characters"));
+		}
+
+		Ok(_type)
+	}
+}
+
+impl Serialize for SchemaType{
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where S: serde::Serializer 
+	{
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for SchemaType{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where D: serde::Deserializer<'de> 
+	{
+		let value = String::deserialize(deserializer)?;
+		value.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+/// An error returned when a type string in the schema is malformed.
+#[derive(Debug,Clone,PartialEq)]
+pub struct ParseTypeError{
+	source: String,
+	message: String
+}
+
+impl ParseTypeError{
+	fn new(source: &str, message: &str) -> Self{
+		Self { 
+			source: String::from(source), 
+			message: String::from(message) 
+		}
+	}
+}
+
+impl std::fmt::Display for ParseTypeError{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f,"invalid type `{}`: {}",self.source,self.message)
+	}
+}
+
+impl std::error::Error for ParseTypeError{}
+
+/// Recursive descent parser for [`SchemaType`] strings.
+struct TypeParser<'a>{
+	source: &'a str,
+	position: usize
+}
+
+impl TypeParser<'_>{
+	fn rest(&self) -> &str{
+		&self.source[self.position..]
+	}
+
+	fn skip_whitespace(&mut self){
+		let rest = self.rest();
+		self.position += rest.len() - rest.trim_start().len();
+	}
+
+	fn eat(&mut self, token: &str) -> bool{
+		self.skip_whitespace();
+		if self.rest().starts_with(token){
+			self.position += token.len();
+			return true;
+		}
+		false
+	}
+
+	fn expect(&mut self, token: &str) -> Result<(),ParseTypeError>{
+		if !self.eat(token){
+			return Err(self.error(&format!("expected `{}`",token)));
+		}
+		Ok(())
+	}
+
+	fn error(&self, message: &str) -> ParseTypeError{
+		ParseTypeError::new(self.source, message)
+	}
+
+	fn identifier(&mut self) -> Result<&str,ParseTypeError>{
+		self.skip_whitespace();
+		let start = self.position;
+		let length = self.rest()
+			.find(|c:char| !(c.is_ascii_alphanumeric() || c == '_'))
+			.unwrap_or(self.rest().len());
+		
+		let ident = &self.source[start..start+length];
+		if ident.is_empty() || ident.starts_with(|c:char| c.is_ascii_digit()){
+			return Err(self.error("expected a type name"));
+		}
+
+		self.position += length;
+		Ok(ident)
+	}
+
+	fn parse_type(&mut self) -> Result<SchemaType,ParseTypeError>{
+		let mut _type = match self.identifier()? {
+			"int" => SchemaType::Int,
+			"float" => SchemaType::Float,
+			"string" => SchemaType::String,
+			"boolean" => SchemaType::Boolean,
+			"map" => {
+				self.expect("<")?;
+				if self.identifier()? != "string"{
+					return Err(self.error("map keys must be of type `string`"));
+				}
+				self.expect(",")?;
+				let value = self.parse_type()?;
+				self.expect(">")?;
+				SchemaType::Map(Box::new(value))
+			},
+			name => SchemaType::Struct(String::from(name))
+		};
+
+		loop {
+			if self.eat("[]"){
+				_type = SchemaType::Array(Box::new(_type));
+			} else if self.eat("?"){
+				_type = SchemaType::Optional(Box::new(_type));
+			} else {
+				break;
+			}
+		}
+
+		Ok(_type)
+	}
 }
 
 #[derive(Debug, Serialize, Deserialize,Clone, Copy,PartialEq)]
@@ -76,30 +603,254 @@ impl SchemaType{
 pub enum HttpMethod {
     Get,
     Post,
+    Put,
     Patch,
     Delete,
+	Head,
+	Options,
 }
 
+impl HttpMethod{
+	/// The method name as it's sent in a request, e.g. `GET`.
+	pub fn as_str(&self) -> &'static str{
+		match self {
+			Self::Get => "GET",
+			Self::Post => "POST",
+			Self::Put => "PUT",
+			Self::Patch => "PATCH",
+			Self::Delete => "DELETE",
+			Self::Head => "HEAD",
+			Self::Options => "OPTIONS",
+		}
+	}
+
+	/// Returns `false` for methods whose requests cannot have a body.
+	pub fn has_request_body(&self) -> bool{
+		!matches!(self,Self::Get | Self::Head)
+	}
+
+	/// Returns `false` for methods whose responses cannot have a body.
+	pub fn has_response_body(&self) -> bool{
+		!matches!(self,Self::Head)
+	}
+}
+
+impl std::fmt::Display for HttpMethod{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
 
 impl ToTokens for HttpMethod{
 	fn to_tokens(&self, tokens: &mut proc_macro2::TokenStream) {
-		match self {
-			Self::Get => {
-				let ident = proc_macro2::Literal::string("GET",);
-				tokens.append(ident);
-			},
-			Self::Post => {
-				let ident = proc_macro2::Literal::string("POST");
-				tokens.append(ident);
-			},
-			Self::Patch => {
-				let ident = proc_macro2::Literal::string("PATCH");
-				tokens.append(ident);
-			},
-			Self::Delete => {
-				let ident = proc_macro2::Literal::string("DELETE");
-				tokens.append(ident);
-			},
+		let literal = proc_macro2::Literal::string(self.as_str());
+		tokens.append(literal);
+	}
+}
+
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	#[test]
+	fn parse_primitive_types(){
+		assert_eq!("int".parse(),Ok(SchemaType::Int));
+		assert_eq!("float".parse(),Ok(SchemaType::Float));
+		assert_eq!("string".parse(),Ok(SchemaType::String));
+		assert_eq!("boolean".parse(),Ok(SchemaType::Boolean));
+		assert_eq!("User".parse(),Ok(SchemaType::Struct("User".to_string())));
+	}
+
+	#[test]
+	fn parse_compound_types(){
+		let tags: SchemaType = "string[]".parse().unwrap();
+		assert_eq!(tags,SchemaType::Array(Box::new(SchemaType::String)));
+		
+		let nickname: SchemaType = "string?".parse().unwrap();
+		assert_eq!(nickname,SchemaType::Optional(Box::new(SchemaType::String)));
+		
+		let metadata: SchemaType = "map<string, int>".parse().unwrap();
+		assert_eq!(metadata,SchemaType::Map(Box::new(SchemaType::Int)));
+		
+		let nested: SchemaType = "map<string,User[]>?".parse().unwrap();
+		assert_eq!(
+			nested,
+			SchemaType::Optional(Box::new(
+				SchemaType::Map(Box::new(
+					SchemaType::Array(Box::new(SchemaType::Struct("User".to_string())))
+				))
+			))
+		);
+	}
+
+	#[test]
+	fn reject_malformed_types(){
+		assert!("map<int, string>".parse::<SchemaType>().is_err());
+		assert!("map<string, int".parse::<SchemaType>().is_err());
+		assert!("string[".parse::<SchemaType>().is_err());
+		assert!("".parse::<SchemaType>().is_err());
+		assert!("1User".parse::<SchemaType>().is_err());
+	}
+
+	#[test]
+	fn type_display_round_trip(){
+		for source in ["int","string[]","boolean?","map<string, float>","User[]?"]{
+			let _type: SchemaType = source.parse().unwrap();
+			assert_eq!(_type.to_string(),source);
 		}
 	}
+
+	#[test]
+	fn find_literal_endpoint(){
+		let schema = ApiSchema::parse(concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs: {}\n",
+			"endpoints:\n",
+			"  get_orders:\n",
+			"    uri: /users/{id}/orders\n",
+			"    method: GET\n",
+			"    params:\n",
+			"      id: int\n",
+			"  get_items:\n",
+			"    uri: /users/{id}/{kind}\n",
+			"    method: GET\n",
+			"    params:\n",
+			"      id: int\n",
+			"      kind: string\n",
+			"  get_me:\n",
+			"    uri: /users/me/{kind}\n",
+			"    method: GET\n",
+			"    params:\n",
+			"      kind: string\n",
+		)).unwrap();
+
+		let name = |path|schema.find_endpoint("GET", path).map(|(name,_)|name.as_str());
+		assert_eq!(name("/users/me/orders"),Some("get_me"));
+		assert_eq!(name("/users/5/orders"),Some("get_orders"));
+		assert_eq!(name("/users/5/invoices"),Some("get_items"));
+		assert_eq!(name("/users/5"),None);
+	}
+
+	#[test]
+	fn parse_schema_fields(){
+		let schema = ApiSchema::parse(concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    tags: string[]\n",
+			"    nickname: string?\n",
+			"    metadata: map<string, int>\n",
+			"endpoints: {}\n",
+		)).unwrap();
+
+		let user = &schema.structs["User"];
+		assert_eq!(user["tags"],SchemaType::Array(Box::new(SchemaType::String)));
+		assert_eq!(user["nickname"],SchemaType::Optional(Box::new(SchemaType::String)));
+		assert_eq!(user["metadata"],SchemaType::Map(Box::new(SchemaType::Int)));
+	}
+
+	#[test]
+	fn reject_duplicate_keys(){
+		let error = ApiSchema::parse(concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    id: int\n",
+			"  User:\n",
+			"    id: string\n",
+			"endpoints: {}\n",
+		)).unwrap_err();
+
+		assert!(error.to_string().contains("duplicate entry"));
+	}
+
+	#[test]
+	fn parse_uri_segments(){
+		let segments = UriSegment::parse("https://example.com/users/{id}/orders/{order_id}").unwrap();
+		assert_eq!(segments,vec![
+			UriSegment::Literal("https://example.com/users/"),
+			UriSegment::Param("id"),
+			UriSegment::Literal("/orders/"),
+			UriSegment::Param("order_id"),
+		]);
+
+		assert!(UriSegment::parse("/users/{id").is_err());
+		assert!(UriSegment::parse("/users/id}").is_err());
+		assert!(UriSegment::parse("/users/{{id}}").is_err());
+	}
+
+	#[test]
+	fn endpoint_path(){
+		let mut endpoint = Endpoint{
+			uri: String::from("https://example.com/users/{id}?page=1"),
+			method: HttpMethod::Get,
+			params: IndexMap::new(),
+			query: IndexMap::new(),
+			input: None,
+			returns: None,
+			errors: IndexMap::new()
+		};
+		assert_eq!(endpoint.path(),"/users/{id}");
+		assert_eq!(endpoint.origin(),Some("https://example.com"));
+
+		endpoint.uri = String::from("https://example.com");
+		assert_eq!(endpoint.path(),"/");
+		assert_eq!(endpoint.origin(),Some("https://example.com"));
+		
+		endpoint.uri = String::from("/orders");
+		assert_eq!(endpoint.path(),"/orders");
+		assert_eq!(endpoint.origin(),None);
+	}
+
+	#[test]
+	fn parse_http_methods(){
+		for method in ["GET","POST","PUT","PATCH","DELETE","HEAD","OPTIONS"]{
+			let parsed: HttpMethod = serde_yaml::from_str(method).unwrap();
+			assert_eq!(parsed.as_str(),method);
+			assert_eq!(quote!{#parsed}.to_string(),format!("{:?}",method));
+		}
+	}
+
+	#[test]
+	fn parse_enums(){
+		let schema = ApiSchema::parse(concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs: {}\n",
+			"enums:\n",
+			"  Status:\n",
+			"    - active\n",
+			"    - suspended\n",
+			"  Shape:\n",
+			"    variants:\n",
+			"      Circle:\n",
+			"        radius: float\n",
+			"  Event:\n",
+			"    tag: kind\n",
+			"    variants:\n",
+			"      Closed: {}\n",
+			"endpoints: {}\n",
+		)).unwrap();
+
+		assert_eq!(
+			schema.enums["Status"],
+			Enum::Simple(vec!["active".to_string(),"suspended".to_string()])
+		);
+
+		let Enum::Tagged(shape) = &schema.enums["Shape"] else {
+			panic!("Expected a tagged union")
+		};
+		assert_eq!(shape.tag,"type");
+		assert_eq!(shape.variants["Circle"]["radius"],SchemaType::Float);
+		
+		let Enum::Tagged(event) = &schema.enums["Event"] else {
+			panic!("Expected a tagged union")
+		};
+		assert_eq!(event.tag,"kind");
+		assert!(event.variants["Closed"].is_empty());
+	}
 }
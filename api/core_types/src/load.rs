@@ -0,0 +1,335 @@
+//! Loading schemas that are split across multiple files.
+//!
+//! A schema can include other files, relative to the including file,
+//! whose structs, enums and endpoints are merged into the root schema.
+//!
+//! ```yaml
+//! # schema.yml
+//! version: 0.1.0
+//! schema_diff: minor
+//! include:
+//!   - users.yml
+//!   - orders/orders.yml
+//!
+//! structs: {}
+//! endpoints: {}
+//! ```
+//!
+//! Included files can only contain `include`, `structs`, `enums` and
+//! `endpoints`.
+//!
+//! ```yaml
+//! # users.yml
+//! structs:
+//!   User:
+//!     id: int
+//! ```
+use std::{collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}};
+use serde::Deserialize;
+use crate::{ApiSchema, Endpoint, Enum, IndexMap, SchemaType};
+
+/// The contents of a file that was loaded as part of a schema.
+#[derive(Debug,Clone,PartialEq, Eq)]
+pub struct SchemaFile{
+	pub path: PathBuf,
+	pub contents: String
+}
+
+/// An error that occurred while loading a schema and its includes.
+#[derive(Debug)]
+pub enum LoadError{
+	Io{
+		path: PathBuf,
+		source: std::io::Error
+	},
+	Parse{
+		path: PathBuf,
+		source: serde_yaml::Error
+	},
+	/// A file includes itself, directly or through other files.
+	IncludeCycle(Vec<PathBuf>),
+	/// The same name is declared in more than one file.
+	Collision{
+		/// The section the name was declared in, e.g. `structs`
+		section: &'static str,
+		name: String,
+		first: PathBuf,
+		second: PathBuf
+	}
+}
+
+impl std::fmt::Display for LoadError{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Io { path, source } => {
+				write!(f,"failed to read {}: {}",path.display(),source)
+			},
+			Self::Parse { path, source } => {
+				write!(f,"failed to parse {}: {}",path.display(),source)
+			},
+			Self::IncludeCycle(paths) => {
+				let paths: Vec<String> = paths.iter()
+					.map(|path|path.display().to_string())
+					.collect();
+				write!(f,"include cycle: {}",paths.join(" -> "))
+			},
+			Self::Collision { section, name, first, second } => {
+				write!(
+					f,
+					"{}.{} is declared in both {} and {}",
+					section,
+					name,
+					first.display(),
+					second.display()
+				)
+			}
+		}
+	}
+}
+
+impl std::error::Error for LoadError{
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::Io { source, .. } => Some(source),
+			Self::Parse { source, .. } => Some(source),
+			_ => None
+		}
+	}
+}
+
+/// The sections of an included file.
+#[derive(Debug,Deserialize,Default)]
+#[serde(deny_unknown_fields)]
+struct SchemaPart{
+	#[serde(default)]
+	include: Vec<String>,
+	#[serde(default)]
+	structs: IndexMap<String, IndexMap<String, SchemaType>>,
+	#[serde(default)]
+	enums: IndexMap<String, Enum>,
+	#[serde(default)]
+	endpoints: IndexMap<String, Endpoint>,
+}
+
+impl ApiSchema{
+	/// Load a schema from a file, merging in every included file.
+	///
+	/// Returns the merged schema along with the contents of every file
+	/// that was loaded, the root file first.
+	pub fn load(path: impl AsRef<Path>) -> Result<(Self,Vec<SchemaFile>),LoadError>{
+		let path = path.as_ref();
+		let contents = read(path)?;
+		let mut schema = ApiSchema::parse(&contents)
+			.map_err(|source|LoadError::Parse { path: path.to_path_buf(), source })?;
+
+		let root_path = canonical(path)?;
+		let mut loader = Loader{
+			files: vec![],
+			stack: vec![root_path.clone()],
+			loaded: HashSet::from([root_path]),
+			origins: HashMap::new()
+		};
+
+		let include = std::mem::take(&mut schema.include);
+		let root = SchemaPart{
+			include: vec![],
+			structs: std::mem::take(&mut schema.structs),
+			enums: std::mem::take(&mut schema.enums),
+			endpoints: std::mem::take(&mut schema.endpoints),
+		};
+		loader.merge(&mut schema, root, path)?;
+		loader.files.push(SchemaFile { path: path.to_path_buf(), contents });
+		loader.include(&mut schema, path, &include)?;
+
+		Ok((schema,loader.files))
+	}
+}
+
+struct Loader{
+	files: Vec<SchemaFile>,
+	/// The canonical paths of the files currently being included, used
+	/// to detect cycles
+	stack: Vec<PathBuf>,
+	/// The canonical paths of every file that has been merged
+	loaded: HashSet<PathBuf>,
+	/// The file each name was declared in
+	origins: HashMap<(&'static str,String),PathBuf>
+}
+
+impl Loader{
+	fn include(&mut self, schema: &mut ApiSchema, parent: &Path, include: &[String]) -> Result<(),LoadError>{
+		let directory = parent.parent().unwrap_or(Path::new(""));
+
+		for file in include{
+			let path = directory.join(file);
+			let canonical_path = canonical(&path)?;
+
+			if let Some(start) = self.stack.iter().position(|item|*item == canonical_path){
+				let mut cycle = self.stack[start..].to_vec();
+				cycle.push(canonical_path);
+				return Err(LoadError::IncludeCycle(cycle));
+			}
+
+			// Files that are included more than once are only merged once
+			if !self.loaded.insert(canonical_path.clone()){
+				continue;
+			}
+
+			let contents = read(&path)?;
+			let mut part = parse_part(&contents)
+				.map_err(|source|LoadError::Parse { path: path.clone(), source })?;
+			
+			let include = std::mem::take(&mut part.include);
+			self.merge(schema, part, &path)?;
+			self.files.push(SchemaFile { path: path.clone(), contents });
+
+			self.stack.push(canonical_path);
+			self.include(schema, &path, &include)?;
+			self.stack.pop();
+		}
+
+		Ok(())
+	}
+
+	fn merge(&mut self, schema: &mut ApiSchema, part: SchemaPart, path: &Path) -> Result<(),LoadError>{
+		for (name,fields) in part.structs{
+			self.declare("structs", &name, path)?;
+			schema.structs.insert(name, fields);
+		}
+
+		for (name,value) in part.enums{
+			self.declare("enums", &name, path)?;
+			schema.enums.insert(name, value);
+		}
+
+		for (name,endpoint) in part.endpoints{
+			self.declare("endpoints", &name, path)?;
+			schema.endpoints.insert(name, endpoint);
+		}
+
+		Ok(())
+	}
+
+	fn declare(&mut self, section: &'static str, name: &str, path: &Path) -> Result<(),LoadError>{
+		let key = (section,String::from(name));
+		if let Some(first) = self.origins.get(&key){
+			return Err(LoadError::Collision {
+				section,
+				name: String::from(name),
+				first: first.clone(),
+				second: path.to_path_buf()
+			});
+		}
+
+		self.origins.insert(key, path.to_path_buf());
+		Ok(())
+	}
+}
+
+fn parse_part(contents: &str) -> Result<SchemaPart,serde_yaml::Error>{
+	// Parsing into a value first rejects duplicate keys
+	serde_yaml::from_str::<serde_yaml::Value>(contents)?;
+	serde_yaml::from_str(contents)
+}
+
+fn read(path: &Path) -> Result<String,LoadError>{
+	fs::read_to_string(path)
+		.map_err(|source|LoadError::Io { path: path.to_path_buf(), source })
+}
+
+fn canonical(path: &Path) -> Result<PathBuf,LoadError>{
+	path.canonicalize()
+		.map_err(|source|LoadError::Io { path: path.to_path_buf(), source })
+}
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	/// Write the files into a fresh directory in the temp folder
+	fn write_files(name: &str, files: &[(&str,&str)]) -> PathBuf{
+		let directory = std::env::temp_dir().join("core_types_load").join(name);
+		let _ = fs::remove_dir_all(&directory);
+		
+		for (path,contents) in files{
+			let path = directory.join(path);
+			fs::create_dir_all(path.parent().unwrap()).unwrap();
+			fs::write(path, contents).unwrap();
+		}
+
+		directory
+	}
+
+	const ROOT: &str = concat!(
+		"version: 0.1.0\n",
+		"schema_diff: none\n",
+		"include:\n",
+		"  - users.yml\n",
+		"  - orders/orders.yml\n",
+		"structs: {}\n",
+		"endpoints: {}\n",
+	);
+
+	#[test]
+	fn merge_included_files(){
+		let directory = write_files("merge_included_files", &[
+			("schema.yml",ROOT),
+			("users.yml","structs:\n  User:\n    id: int\n"),
+			("orders/orders.yml","include:\n  - ../users.yml\n  - items.yml\nstructs:\n  Order:\n    user: User\n"),
+			("orders/items.yml","enums:\n  Item:\n    - book\n"),
+		]);
+
+		let (schema,files) = ApiSchema::load(directory.join("schema.yml")).unwrap();
+		
+		assert!(schema.include.is_empty());
+		assert!(schema.structs.contains_key("User"));
+		assert!(schema.structs.contains_key("Order"));
+		assert!(schema.enums.contains_key("Item"));
+		assert_eq!(files.len(),4);
+		assert_eq!(files[0].path,directory.join("schema.yml"));
+	}
+
+	#[test]
+	fn include_cycle(){
+		let directory = write_files("include_cycle", &[
+			("schema.yml",ROOT),
+			("users.yml","include:\n  - orders/orders.yml\n"),
+			("orders/orders.yml","include:\n  - ../users.yml\n"),
+		]);
+
+		let error = ApiSchema::load(directory.join("schema.yml")).unwrap_err();
+		let LoadError::IncludeCycle(cycle) = error else {
+			panic!("Expected an include cycle, got {error}");
+		};
+		assert_eq!(cycle.len(),3);
+		assert_eq!(cycle.first(),cycle.last());
+	}
+
+	#[test]
+	fn name_collision(){
+		let directory = write_files("name_collision", &[
+			("schema.yml",ROOT),
+			("users.yml","structs:\n  User:\n    id: int\n"),
+			("orders/orders.yml","structs:\n  User:\n    id: string\n"),
+		]);
+
+		let error = ApiSchema::load(directory.join("schema.yml")).unwrap_err();
+		let LoadError::Collision { section, name, .. } = error else {
+			panic!("Expected a name collision, got {error}");
+		};
+		assert_eq!(section,"structs");
+		assert_eq!(name,"User");
+	}
+
+	#[test]
+	fn reject_schema_fields_in_includes(){
+		let directory = write_files("reject_schema_fields_in_includes", &[
+			("schema.yml",ROOT),
+			("users.yml","version: 0.2.0\n"),
+			("orders/orders.yml","{}\n"),
+		]);
+
+		let error = ApiSchema::load(directory.join("schema.yml")).unwrap_err();
+		assert!(matches!(error,LoadError::Parse { .. }));
+	}
+}
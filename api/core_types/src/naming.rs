@@ -0,0 +1,176 @@
+//! Converting schema names into identifiers for the generated code.
+//!
+//! Schema names can be written in any case, each language gets them in its
+//! own convention. Fields, parameters and methods are snake case in rust
+//! and camel case in typescript, types and enum variants are pascal case
+//! in both. The wire format always uses the names from the schema.
+
+/// The naming convention of a generated language.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Convention{
+	Rust,
+	Typescript
+}
+
+impl Convention{
+	pub const ALL: [Self;2] = [Self::Rust,Self::Typescript];
+
+	/// The name of a field, parameter or method.
+	///
+	/// # Example
+	/// ```
+	/// use core_types::naming::Convention;
+	///
+	/// assert_eq!(Convention::Rust.member("createdAt"),"created_at");
+	/// assert_eq!(Convention::Typescript.member("created_at"),"createdAt");
+	/// ```
+	pub fn member(&self, name: &str) -> String{
+		match self {
+			Self::Rust => snake_case(name),
+			Self::Typescript => camel_case(name)
+		}
+	}
+
+	/// The name of a type or enum variant.
+	pub fn type_name(&self, name: &str) -> String{
+		pascal_case(name)
+	}
+}
+
+impl std::fmt::Display for Convention{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::Rust => f.write_str("rust"),
+			Self::Typescript => f.write_str("typescript")
+		}
+	}
+}
+
+/// Split a name into words, on underscores and where a lowercase letter or
+/// digit is followed by an uppercase letter.
+fn words(name: &str) -> Vec<&str>{
+	let mut words = vec![];
+	for part in name.split('_'){
+		let mut start = 0;
+		let mut previous: Option<char> = None;
+		for (index,c) in part.char_indices(){
+			if let Some(previous) = previous
+				&& c.is_uppercase()
+				&& (previous.is_lowercase() || previous.is_ascii_digit())
+			{
+				words.push(&part[start..index]);
+				start = index;
+			}
+			previous = Some(c);
+		}
+		if start < part.len(){
+			words.push(&part[start..]);
+		}
+	}
+	words
+}
+
+/// Uppercase the first character of the word.
+fn capitalize(word: &str) -> String{
+	let mut chars = word.chars();
+	match chars.next() {
+		Some(first) => first.to_uppercase().chain(chars).collect(),
+		None => String::new()
+	}
+}
+
+/// Convert a name to pascal case, `get_user` becomes `GetUser`.
+///
+/// # Example
+/// ```
+/// use core_types::naming::pascal_case;
+///
+/// assert_eq!(pascal_case("get_user"),"GetUser");
+/// assert_eq!(pascal_case("getUser"),"GetUser");
+/// assert_eq!(pascal_case("User"),"User");
+/// ```
+pub fn pascal_case(name: &str) -> String{
+	words(name).into_iter()
+		.map(capitalize)
+		.collect()
+}
+
+/// Convert a name to camel case, `created_at` becomes `createdAt`.
+///
+/// # Example
+/// ```
+/// use core_types::naming::camel_case;
+///
+/// assert_eq!(camel_case("created_at"),"createdAt");
+/// assert_eq!(camel_case("ID"),"id");
+/// ```
+pub fn camel_case(name: &str) -> String{
+	let mut words = words(name).into_iter();
+	let Some(first) = words.next() else {
+		return String::new();
+	};
+
+	let mut name = first.to_lowercase();
+	name.extend(words.map(capitalize));
+	name
+}
+
+/// Convert a name to snake case, `createdAt` becomes `created_at`.
+///
+/// # Example
+/// ```
+/// use core_types::naming::snake_case;
+///
+/// assert_eq!(snake_case("createdAt"),"created_at");
+/// assert_eq!(snake_case("created_at"),"created_at");
+/// ```
+pub fn snake_case(name: &str) -> String{
+	words(name).into_iter()
+		.map(str::to_lowercase)
+		.collect::<Vec<_>>()
+		.join("_")
+}
+
+/// The name of the type holding an endpoint's declared errors,
+/// `get_user` becomes `GetUserError`.
+pub fn error_type_name(endpoint: &str) -> String{
+	format!("{}Error",pascal_case(endpoint))
+}
+
+/// The name of the type holding an endpoint's query parameters,
+/// `list_users` becomes `ListUsersQuery`.
+pub fn query_type_name(endpoint: &str) -> String{
+	format!("{}Query",pascal_case(endpoint))
+}
+
+/// The name of the type holding an endpoint's path parameters,
+/// `get_user` becomes `GetUserPath`.
+pub fn path_type_name(endpoint: &str) -> String{
+	format!("{}Path",pascal_case(endpoint))
+}
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	#[test]
+	fn split_words(){
+		assert_eq!(words("created_at"),vec!["created","at"]);
+		assert_eq!(words("createdAt"),vec!["created","At"]);
+		assert_eq!(words("HTTPServer"),vec!["HTTPServer"]);
+		assert_eq!(words("page2Size"),vec!["page2","Size"]);
+		assert_eq!(words("__private"),vec!["private"]);
+		assert!(words("_").is_empty());
+	}
+
+	#[test]
+	fn conversions(){
+		for name in ["created_at","createdAt","CreatedAt"]{
+			assert_eq!(snake_case(name),"created_at");
+			assert_eq!(camel_case(name),"createdAt");
+			assert_eq!(pascal_case(name),"CreatedAt");
+		}
+		assert_eq!(snake_case("id"),"id");
+		assert_eq!(camel_case("user_ID"),"userID");
+	}
+}
@@ -0,0 +1,158 @@
+//! The body of the responses sent when the server rejects a request
+//! before it reaches the handler, or when the handler's response doesn't
+//! match the schema.
+//!
+//! ```json
+//! {
+//! 	"code": "incompatible_schema",
+//! 	"message": "the client's schema is not compatible with the server's schema",
+//! 	"expected_checksum": "5e3f...",
+//! 	"received_checksum": "a01c...",
+//! 	"server_version": "1.2.0",
+//! 	"client_version": "2.0.0"
+//! }
+//! ```
+//!
+//! Requests whose body doesn't match the endpoint's input list every
+//! invalid value.
+//!
+//! ```json
+//! {
+//! 	"code": "invalid_body",
+//! 	"message": "the request body does not match UserPayload",
+//! 	"errors": [{ "path": "$.name", "message": "missing field" }]
+//! }
+//! ```
+use std::fmt::Display;
+use serde::{Deserialize, Serialize};
+use crate::{FieldError, Version};
+
+/// Why a request was rejected.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash,Serialize,Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectionCode{
+	/// The request has no checksum header
+	MissingChecksum,
+	/// The checksum header is not valid ascii
+	InvalidChecksum,
+	/// The checksum doesn't match and the versions are too far apart
+	IncompatibleSchema,
+	/// The request body doesn't match the endpoint's input
+	InvalidBody,
+	/// The request body is bigger than the server reads
+	BodyTooLarge,
+	/// The response body doesn't match the endpoint's return type
+	InvalidResponse
+}
+
+impl RejectionCode{
+	/// The status code of the response.
+	pub fn status(&self) -> u16{
+		match self {
+			Self::MissingChecksum | Self::InvalidChecksum => 400,
+			Self::IncompatibleSchema | Self::InvalidBody => 422,
+			Self::BodyTooLarge => 413,
+			Self::InvalidResponse => 500
+		}
+	}
+
+	pub fn as_str(&self) -> &'static str{
+		match self {
+			Self::MissingChecksum => "missing_checksum",
+			Self::InvalidChecksum => "invalid_checksum",
+			Self::IncompatibleSchema => "incompatible_schema",
+			Self::InvalidBody => "invalid_body",
+			Self::BodyTooLarge => "body_too_large",
+			Self::InvalidResponse => "invalid_response",
+		}
+	}
+
+	/// Every rejection code.
+	pub fn all() -> [Self;6]{
+		[
+			Self::MissingChecksum,
+			Self::InvalidChecksum,
+			Self::IncompatibleSchema,
+			Self::InvalidBody,
+			Self::BodyTooLarge,
+			Self::InvalidResponse
+		]
+	}
+}
+
+impl Display for RejectionCode{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f,"{}",self.as_str())
+	}
+}
+
+/// The body of a rejected request.
+#[derive(Debug,Clone,PartialEq,Eq,Serialize,Deserialize)]
+pub struct Rejection{
+	pub code: RejectionCode,
+	pub message: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub expected_checksum: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub received_checksum: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub server_version: Option<Version>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub client_version: Option<Version>,
+	/// The values in the body that don't match their types
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub errors: Vec<FieldError>
+}
+
+impl Rejection{
+	/// Create a rejection with only a code and message.
+	pub fn new(code: RejectionCode, message: &str) -> Self{
+		Self{
+			code,
+			message: String::from(message),
+			expected_checksum: None,
+			received_checksum: None,
+			server_version: None,
+			client_version: None,
+			errors: vec![]
+		}
+	}
+
+	/// Parse the body of a response, returns `None` if the body is not a
+	/// rejection.
+	pub fn parse(body: &str) -> Option<Self>{
+		serde_json::from_str(body).ok()
+	}
+}
+
+impl Display for Rejection{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f,"{}: {}",self.code,self.message)?;
+		if let (Some(server),Some(client)) = (&self.server_version,&self.client_version){
+			write!(f," (server {}, client {})",server,client)?;
+		}
+		Ok(())
+	}
+}
+
+impl std::error::Error for Rejection{}
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	#[test]
+	fn rejection_json(){
+		let mut rejection = Rejection::new(RejectionCode::IncompatibleSchema, "incompatible");
+		rejection.server_version = Some(Version::new(1, 2, 0));
+		rejection.client_version = Some(Version::new(2, 0, 0));
+
+		let body = serde_json::to_string(&rejection).unwrap();
+		assert_eq!(
+			body,
+			r#"{"code":"incompatible_schema","message":"incompatible","server_version":"1.2.0","client_version":"2.0.0"}"#
+		);
+		assert_eq!(Rejection::parse(&body),Some(rejection));
+		assert_eq!(Rejection::parse(r#"{"message":"Not found"}"#),None);
+	}
+}
@@ -0,0 +1,1653 @@
+//! Semantic validation of an [`ApiSchema`].
+//!
+//! Parsing only checks that the schema has the right shape, this module
+//! checks that it makes sense, that every type that is referenced exists,
+//! that names can be used as identifiers in the generated code and so on.
+//! All the problems are collected so they can be reported at once.
+use std::{collections::{HashMap, HashSet}, path::PathBuf};
+use crate::{naming::{error_type_name, pascal_case, path_type_name, query_type_name, Convention}, ApiSchema, CodegenConfig, Endpoint, Enum, SchemaFile, SchemaType, UriSegment};
+
+/// Names that cannot be used as identifiers in rust.
+const RUST_KEYWORDS: &[&str] = &[
+	"as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+	"enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop",
+	"match", "mod", "move", "mut", "pub", "ref", "return", "self", "Self",
+	"static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+	"where", "while", "abstract", "become", "box", "do", "final", "gen", "macro",
+	"override", "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Names that cannot be used as type names in typescript, including the
+/// global types that the generated code uses.
+const TS_RESERVED_TYPES: &[&str] = &[
+	"any", "bigint", "boolean", "class", "never", "null", "number", "object",
+	"string", "symbol", "undefined", "unknown", "void", "interface", "function",
+	"Promise", "Record",
+];
+
+/// Names that are already used by the schema's primitive types.
+const SCHEMA_PRIMITIVES: &[&str] = &["int", "float", "string", "boolean", "map"];
+
+/// Names of the items generated alongside the schema's types.
+const GENERATED_TYPES: &[&str] = &[
+	"Api", "ApiError", "Client", "ClientError", "ClientOptions", "Rejection", "RejectionError",
+	"ValidationError", "ValidationIssue", "Validator",
+];
+
+/// Types whose `validate` function is part of the typescript runtime, so
+/// structs and enums can't generate one with the same name.
+const TS_RUNTIME_VALIDATORS: &[&str] = &[
+	"Int", "Float", "String", "Boolean", "Optional", "Array", "Map", "OneOf",
+];
+
+/// Methods of the generated rust client that endpoints can't be named.
+const RUST_CLIENT_METHODS: &[&str] = &["new", "with_client", "url"];
+
+/// Members of the generated typescript client that endpoints can't be named.
+const TS_CLIENT_MEMBERS: &[&str] = &["constructor", "checksum", "baseUrl", "headers"];
+
+/// Parameters and variables of the generated client methods that path 
+/// parameters can't be named.
+const CLIENT_METHOD_LOCALS: &[&str] = &[
+	"query", "payload", "url", "params", "search", "response", "body", "error",
+	"segments", "pairs", "status", "rejection", "fetch", "isRejection",
+];
+
+/// A line and column in the schema file, both starting at 1.
+#[derive(Debug,Clone,Copy,PartialEq, Eq)]
+pub struct Location{
+	pub line: usize,
+	pub column: usize
+}
+
+/// A single problem found in the schema.
+#[derive(Debug,Clone,PartialEq, Eq)]
+pub struct Diagnostic{
+	/// The dotted path to the offending item, e.g. `structs.User.email`.
+	pub path: String,
+	pub message: String,
+	/// The file the offending item was declared in, if the schema was loaded from
+	/// more than one file.
+	pub file: Option<PathBuf>,
+	pub location: Option<Location>
+}
+
+impl std::fmt::Display for Diagnostic{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f,"{}: {}",self.path,self.message)?;
+		if let Some(file) = &self.file{
+			write!(f," in {}",file.display())?;
+		}
+		if let Some(location) = self.location{
+			write!(f," at line {} column {}",location.line,location.column)?;
+		}
+		Ok(())
+	}
+}
+
+/// The error returned when a schema fails validation, contains every
+/// [`Diagnostic`] that was found.
+#[derive(Debug,Clone,PartialEq, Eq)]
+pub struct ValidationError{
+	pub diagnostics: Vec<Diagnostic>
+}
+
+impl std::fmt::Display for ValidationError{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f,"schema has {} error(s)",self.diagnostics.len())?;
+		for diagnostic in &self.diagnostics{
+			write!(f,"\n  {}",diagnostic)?;
+		}
+		Ok(())
+	}
+}
+
+impl std::error::Error for ValidationError{}
+
+/// Validate a schema, `source` is the yaml the schema was parsed from and
+/// is used to attach line and column numbers to the diagnostics.
+///
+/// # Example
+/// ```
+/// use core_types::{ApiSchema,validate};
+///
+/// let source = concat!(
+/// 	"version: 0.1.0\n",
+/// 	"schema_diff: none\n",
+/// 	"structs:\n",
+/// 	"  User:\n",
+/// 	"    manager: Usr\n",
+/// 	"endpoints: {}\n",
+/// );
+/// let schema = ApiSchema::parse(source).unwrap();
+/// let error = validate::validate(&schema, Some(source)).unwrap_err();
+///
+/// assert_eq!(error.diagnostics[0].path,"structs.User.manager");
+/// ```
+pub fn validate(schema: &ApiSchema, source: Option<&str>) -> Result<(),ValidationError>{
+	let mut diagnostics = check(schema);
+	if diagnostics.is_empty(){
+		return Ok(());
+	}
+
+	if let Some(source) = source{
+		for diagnostic in &mut diagnostics{
+			let path: Vec<&str> = diagnostic.path.split('.').collect();
+			diagnostic.location = locate(source, &path).map(|(_,location)|location);
+		}
+	}
+
+	Err(ValidationError { diagnostics })
+}
+
+/// Validate a schema that was loaded from multiple files with 
+/// [`ApiSchema::load`], each diagnostic points to the file that the 
+/// offending item was declared in.
+pub fn validate_files(schema: &ApiSchema, files: &[SchemaFile]) -> Result<(),ValidationError>{
+	let mut diagnostics = check(schema);
+	if diagnostics.is_empty(){
+		return Ok(());
+	}
+
+	for diagnostic in &mut diagnostics{
+		let path: Vec<&str> = diagnostic.path.split('.').collect();
+		let found = files.iter()
+			.filter_map(|file|{
+				let (depth,location) = locate(&file.contents, &path)?;
+				Some((depth,location,file))
+			})
+			.max_by_key(|(depth,_,_)|*depth);
+
+		if let Some((_,location,file)) = found{
+			diagnostic.location = Some(location);
+			if files.len() > 1{
+				diagnostic.file = Some(file.path.clone());
+			}
+		}
+	}
+
+	Err(ValidationError { diagnostics })
+}
+
+fn check(schema: &ApiSchema) -> Vec<Diagnostic>{
+	let mut validator = Validator{
+		schema,
+		diagnostics: vec![]
+	};
+
+	validator.check_names();
+	validator.check_conventions();
+	validator.check_references();
+	validator.check_cycles();
+	validator.check_endpoints();
+	validator.check_codegen();
+
+	validator.diagnostics
+}
+
+/// Returns `true` if `name` is a valid identifier in every generated language.
+pub fn is_identifier(name: &str) -> bool{
+	let mut chars = name.chars();
+	let Some(first) = chars.next() else {
+		return false;
+	};
+
+	(first.is_ascii_alphabetic() || first == '_')
+		&& chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+struct Validator<'a>{
+	schema: &'a ApiSchema,
+	diagnostics: Vec<Diagnostic>
+}
+
+impl Validator<'_>{
+	fn error(&mut self, path: String, message: String){
+		self.diagnostics.push(Diagnostic { path, message, file: None, location: None });
+	}
+
+	/// Check that a name can be used as a field, method or enum value.
+	fn check_identifier(&mut self, path: String, name: &str){
+		if !is_identifier(name){
+			self.error(path, format!("`{}` is not a valid identifier",name));
+		} else if RUST_KEYWORDS.contains(&name){
+			self.error(path, format!("`{}` is a reserved keyword",name));
+		}
+	}
+
+	/// Check that a name can be used as a type name, returns `false` if 
+	/// it can't.
+	fn check_type_name(&mut self, path: String, name: &str) -> bool{
+		if !is_identifier(name){
+			self.error(path, format!("`{}` is not a valid type name",name));
+			return false;
+		}
+		if RUST_KEYWORDS.contains(&name)
+			|| TS_RESERVED_TYPES.contains(&name)
+			|| SCHEMA_PRIMITIVES.contains(&name)
+			|| GENERATED_TYPES.contains(&name)
+		{
+			self.error(path, format!("`{}` is a reserved type name",name));
+			return false;
+		}
+		let converted = pascal_case(name);
+		if RUST_KEYWORDS.contains(&converted.as_str()) 
+			|| TS_RESERVED_TYPES.contains(&converted.as_str())
+			|| GENERATED_TYPES.contains(&converted.as_str())
+		{
+			self.error(path, format!("`{}` becomes the reserved type name `{}`",name,converted));
+			return false;
+		}
+		true
+	}
+
+	/// Check that the typescript validator of a struct or enum doesn't
+	/// replace one of the runtime's validators.
+	fn check_validator_name(&mut self, path: String, name: &str){
+		let converted = pascal_case(name);
+		if TS_RUNTIME_VALIDATORS.contains(&converted.as_str()){
+			self.error(path, format!("the validator `validate{}` conflicts with a generated function",converted));
+		}
+	}
+
+	fn check_names(&mut self){
+		let schema = self.schema;
+
+		for (name,fields) in &schema.structs{
+			if self.check_type_name(format!("structs.{name}"), name){
+				self.check_validator_name(format!("structs.{name}"), name);
+			}
+			for field in fields.keys(){
+				self.check_identifier(format!("structs.{name}.{field}"), field);
+			}
+		}
+
+		for (name,value) in &schema.enums{
+			let path = format!("enums.{name}");
+			if self.check_type_name(path.clone(), name){
+				self.check_validator_name(path.clone(), name);
+			}
+
+			if schema.structs.contains_key(name){
+				self.error(path.clone(), format!("`{}` is defined as both a struct and an enum",name));
+			}
+
+			match value {
+				Enum::Simple(values) => {
+					let mut seen = HashSet::new();
+					for value in values{
+						self.check_identifier(path.clone(), value);
+						if !seen.insert(value){
+							self.error(path.clone(), format!("duplicate enum value `{}`",value));
+						}
+					}
+				},
+				Enum::Tagged(union) => {
+					for (variant,fields) in &union.variants{
+						let path = format!("{path}.variants.{variant}");
+						self.check_type_name(path.clone(), variant);
+						if fields.contains_key(&union.tag){
+							self.error(
+								path.clone(),
+								format!("field `{}` conflicts with the enum's tag field",union.tag)
+							);
+						}
+						for field in fields.keys(){
+							self.check_identifier(format!("{path}.{field}"), field);
+						}
+					}
+				}
+			}
+		}
+
+		for name in schema.endpoints.keys(){
+			self.check_identifier(format!("endpoints.{name}"), name);
+
+			let method = Convention::Rust.member(name);
+			if RUST_CLIENT_METHODS.contains(&method.as_str()){
+				self.error(
+					format!("endpoints.{name}"), 
+					format!("`{}` conflicts with the rust client's `{}` method",name,method)
+				);
+			}
+
+			let member = Convention::Typescript.member(name);
+			if TS_CLIENT_MEMBERS.contains(&member.as_str()){
+				self.error(
+					format!("endpoints.{name}"), 
+					format!("`{}` conflicts with the typescript client's `{}` member",name,member)
+				);
+			}
+		}
+	}
+
+	/// Names that are different in the schema can be the same once they're
+	/// converted to each language's naming convention.
+	fn check_conventions(&mut self){
+		let schema = self.schema;
+
+		let types = schema.structs.keys()
+			.map(|name|(format!("structs.{name}"),name.as_str()))
+			.chain(schema.enums.keys().map(|name|(format!("enums.{name}"),name.as_str())));
+		self.check_collisions(types, "", pascal_case);
+
+		for (name,fields) in &schema.structs{
+			self.check_members(&format!("structs.{name}"), fields.keys());
+		}
+
+		for (name,value) in &schema.enums{
+			let path = format!("enums.{name}");
+			match value {
+				// The values are only variant names in rust, typescript uses 
+				// the values as they are
+				Enum::Simple(values) => {
+					let values = values.iter().map(|value|(path.clone(),value.as_str()));
+					self.check_collisions(values, " in rust", pascal_case);
+				},
+				Enum::Tagged(union) => {
+					let variants = union.variants.keys()
+						.map(|variant|(format!("{path}.variants.{variant}"),variant.as_str()));
+					self.check_collisions(variants, " in rust", pascal_case);
+
+					let tag = Convention::Typescript.member(&union.tag);
+					for (variant,fields) in &union.variants{
+						let path = format!("{path}.variants.{variant}");
+						self.check_members(&path, fields.keys());
+						for field in fields.keys(){
+							if field != &union.tag && Convention::Typescript.member(field) == tag{
+								self.error(
+									format!("{path}.{field}"), 
+									format!("`{}` and the tag field `{}` are both `{}` in typescript",field,union.tag,tag)
+								);
+							}
+						}
+					}
+				}
+			}
+		}
+
+		self.check_members("endpoints", schema.endpoints.keys());
+		for (name,endpoint) in &schema.endpoints{
+			self.check_members(&format!("endpoints.{name}.params"), endpoint.params.keys());
+			self.check_members(&format!("endpoints.{name}.query"), endpoint.query.keys());
+		}
+	}
+
+	/// Check that fields, parameters or methods are still distinct, valid 
+	/// identifiers in every language.
+	fn check_members<'n>(&mut self, path: &str, names: impl IntoIterator<Item = &'n String>){
+		let names: Vec<&String> = names.into_iter().collect();
+		for convention in Convention::ALL{
+			let pairs = names.iter().map(|name|(format!("{path}.{name}"),name.as_str()));
+			self.check_collisions(pairs, &format!(" in {convention}"), |name|convention.member(name));
+
+			for name in &names{
+				let converted = convention.member(name);
+				if converted == name.as_str(){
+					continue;
+				}
+				let is_keyword = convention == Convention::Rust && RUST_KEYWORDS.contains(&converted.as_str());
+				if !is_identifier(&converted) || is_keyword{
+					self.error(
+						format!("{path}.{name}"), 
+						format!("`{}` becomes `{}` in {}, which is not a valid identifier",name,converted,convention)
+					);
+				}
+			}
+		}
+	}
+
+	/// Report the names that are converted to the same identifier, the
+	/// `context` is appended to the message e.g. ` in rust`.
+	fn check_collisions<'n>(
+		&mut self, 
+		names: impl IntoIterator<Item = (String,&'n str)>, 
+		context: &str,
+		convert: impl Fn(&str) -> String
+	){
+		let mut names: Vec<_> = names.into_iter().collect();
+		names.sort_by_key(|(_,name)|*name);
+
+		let mut seen: HashMap<String,&str> = HashMap::new();
+		for (path,name) in names{
+			let converted = convert(name);
+			match seen.get(&converted) {
+				// Identical names are reported where they're defined
+				Some(other) if *other == name => {},
+				Some(other) => self.error(
+					path, 
+					format!("`{}` and `{}` are both `{}`{}",other,name,converted,context)
+				),
+				None => {
+					seen.insert(converted, name);
+				}
+			}
+		}
+	}
+
+	fn check_codegen(&mut self){
+		let codegen = &self.schema.codegen;
+		let rust = &codegen.rust;
+
+		let client_names = [
+			("codegen.client_name",&codegen.client_name),
+			("codegen.rust.client_name",&rust.client_name),
+			("codegen.typescript.client_name",&codegen.typescript.client_name),
+		];
+		for (path,name) in client_names{
+			let Some(name) = name else {
+				continue;
+			};
+			if !is_identifier(name){
+				self.error(path.to_string(), format!("`{}` is not a valid type name",name));
+			} else if RUST_KEYWORDS.contains(&name.as_str()) 
+				|| TS_RESERVED_TYPES.contains(&name.as_str())
+				|| (GENERATED_TYPES.contains(&name.as_str()) && name != CodegenConfig::DEFAULT_CLIENT_NAME)
+			{
+				self.error(path.to_string(), format!("`{}` is a reserved type name",name));
+			} else if self.is_defined(name){
+				self.error(path.to_string(), format!("`{}` conflicts with an existing type",name));
+			}
+		}
+
+		if let Some(base_url) = &codegen.base_url
+			&& !is_base_url(base_url)
+		{
+			self.error(
+				"codegen.base_url".to_string(), 
+				format!("`{}` is not an absolute http or https url",base_url)
+			);
+		}
+
+		if codegen.typescript.output.as_ref().is_some_and(|output|output.trim().is_empty()){
+			self.error("codegen.typescript.output".to_string(), String::from("output cannot be empty"));
+		}
+
+		for derive in &rust.derives{
+			self.check_derive("codegen.rust.derives".to_string(), derive);
+		}
+		for attribute in &rust.attributes{
+			self.check_attribute("codegen.rust.attributes".to_string(), attribute);
+		}
+
+		for (name,config) in &rust.structs{
+			let path = format!("codegen.rust.structs.{name}");
+			if !self.schema.structs.contains_key(name){
+				self.error(path.clone(), format!("unknown struct `{}`",name));
+			}
+			for derive in &config.derives{
+				self.check_derive(format!("{path}.derives"), derive);
+			}
+			for attribute in &config.attributes{
+				self.check_attribute(format!("{path}.attributes"), attribute);
+			}
+		}
+	}
+
+	/// Fields are sent with their schema names, which the typescript 
+	/// client, the checksum and the body validation all rely on.
+	fn check_attribute(&mut self, path: String, attribute: &str){
+		if attribute.trim_start().starts_with("serde") && attribute.contains("rename"){
+			self.error(path, format!("`{}` would change the names sent on the wire",attribute));
+		}
+	}
+
+	/// Derives are paths to a derive macro, e.g. `Hash` or `serde::Serialize`
+	fn check_derive(&mut self, path: String, derive: &str){
+		let is_path = derive.strip_prefix("::")
+			.unwrap_or(derive)
+			.split("::")
+			.all(is_identifier);
+		if !is_path{
+			self.error(path, format!("`{}` is not a valid derive",derive));
+		}
+	}
+
+	fn is_defined(&self, name: &str) -> bool{
+		self.schema.structs.contains_key(name) || self.schema.enums.contains_key(name)
+	}
+
+	/// Returns `true` if a struct or enum is generated as `type_name`.
+	fn generates_type(&self, type_name: &str) -> bool{
+		self.schema.structs.keys()
+			.chain(self.schema.enums.keys())
+			.any(|name|pascal_case(name) == type_name)
+	}
+
+	fn check_type(&mut self, path: String, _type: &SchemaType){
+		match _type {
+			SchemaType::Array(inner) |
+			SchemaType::Optional(inner) |
+			SchemaType::Map(inner) => self.check_type(path, inner),
+			SchemaType::Struct(name) if !self.is_defined(name) => {
+				self.error(path, format!("unknown type `{}`",name));
+			},
+			_ => {}
+		}
+	}
+
+	fn check_references(&mut self){
+		let schema = self.schema;
+
+		for (name,fields) in &schema.structs{
+			for (field,_type) in fields{
+				self.check_type(format!("structs.{name}.{field}"), _type);
+			}
+		}
+
+		for (name,value) in &schema.enums{
+			if let Enum::Tagged(union) = value{
+				for (variant,fields) in &union.variants{
+					for (field,_type) in fields{
+						self.check_type(format!("enums.{name}.variants.{variant}.{field}"), _type);
+					}
+				}
+			}
+		}
+
+		for (name,endpoint) in &schema.endpoints{
+			if let Some(input) = &endpoint.input{
+				self.check_type(format!("endpoints.{name}.input"), input);
+			}
+			if let Some(returns) = &endpoint.returns{
+				self.check_type(format!("endpoints.{name}.returns"), returns);
+			}
+			for (status,_type) in &endpoint.errors{
+				self.check_type(format!("endpoints.{name}.errors.{status}"), _type);
+			}
+		}
+	}
+
+	fn check_endpoints(&mut self){
+		let schema = self.schema;
+
+		let headers = [("checksum_header",&schema.checksum_header),("version_header",&schema.version_header)];
+		for (path,header) in headers{
+			if let Some(header) = header
+				&& !is_header_name(header)
+			{
+				self.error(path.to_string(), format!("`{}` is not a valid header name",header));
+			}
+		}
+		if schema.checksum_header().eq_ignore_ascii_case(schema.version_header()){
+			self.error(
+				"version_header".to_string(), 
+				format!("the checksum and version are both sent in `{}`",schema.version_header())
+			);
+		}
+
+		// Two endpoints can't be served from the same route, and the router
+		// can't tell paths apart that only differ in their parameter names
+		let mut names: Vec<&String> = schema.endpoints.keys().collect();
+		names.sort();
+		let mut routes: HashMap<(&str,String),&str> = HashMap::new();
+		let mut paths: HashMap<String,(&str,&str)> = HashMap::new();
+		for name in names{
+			let endpoint = &schema.endpoints[name];
+			let shape = route_shape(endpoint.path());
+			if let Some(other) = routes.insert((endpoint.method.as_str(),shape.clone()), name){
+				self.error(
+					format!("endpoints.{name}"), 
+					format!("`{}` and `{}` are both {} {}",other,name,endpoint.method,endpoint.path())
+				);
+			}
+			match paths.get(&shape) {
+				Some((other,path)) if *path != endpoint.path() => self.error(
+					format!("endpoints.{name}.uri"), 
+					format!("`{}` and `{}` name the parameters of the same path differently, `{}` and `{}`",other,name,path,endpoint.path())
+				),
+				Some(_) => {},
+				None => {
+					paths.insert(shape, (name,endpoint.path()));
+				}
+			}
+		}
+
+		for (name,endpoint) in &schema.endpoints{
+			if let Err(message) = check_uri(&endpoint.uri){
+				self.error(format!("endpoints.{name}.uri"), message);
+			}
+			self.check_path_params(name, endpoint);
+
+			if !endpoint.method.has_request_body() && endpoint.input.is_some(){
+				self.error(
+					format!("endpoints.{name}.input"), 
+					format!("{} requests cannot have a body, use query parameters instead",endpoint.method)
+				);
+			}
+			if !endpoint.method.has_response_body() && endpoint.returns.is_some(){
+				self.error(
+					format!("endpoints.{name}.returns"), 
+					format!("{} responses cannot have a body",endpoint.method)
+				);
+			}
+			
+			self.check_errors(name, endpoint);
+			
+			if !endpoint.query.is_empty(){
+				self.check_generated_type(format!("endpoints.{name}.query"), "query", &query_type_name(name));
+			}
+			for (param,_type) in &endpoint.query{
+				let path = format!("endpoints.{name}.query.{param}");
+				self.check_identifier(path.clone(), param);
+				
+				let is_valid = match _type {
+					SchemaType::Optional(inner) |
+					SchemaType::Array(inner) => inner.is_primitive(),
+					_type => _type.is_primitive()
+				};
+				if !is_valid{
+					self.error(
+						path, 
+						format!("query parameters must be a primitive, optional primitive or list of primitives, found `{}`",_type)
+					);
+				}
+			}
+		}
+	}
+
+	fn check_errors(&mut self, name: &str, endpoint: &Endpoint){
+		if endpoint.errors.is_empty(){
+			return;
+		}
+
+		self.check_generated_type(format!("endpoints.{name}.errors"), "error", &error_type_name(name));
+
+		for status in endpoint.errors.keys(){
+			let path = format!("endpoints.{name}.errors.{status}");
+			if !(400..=599).contains(status){
+				self.error(path, format!("`{}` is not an error status code",status));
+			} else if !endpoint.method.has_response_body(){
+				self.error(path, format!("{} responses cannot have a body",endpoint.method));
+			}
+		}
+	}
+
+	/// Check that a type generated for an endpoint, e.g. `GetUserQuery`,
+	/// doesn't conflict with the schema's types.
+	fn check_generated_type(&mut self, path: String, kind: &str, type_name: &str){
+		if self.generates_type(type_name) || GENERATED_TYPES.contains(&type_name){
+			self.error(path, format!("the generated {} type `{}` conflicts with an existing type",kind,type_name));
+		}
+	}
+
+	fn check_path_params(&mut self, name: &str, endpoint: &Endpoint){
+		let params = match endpoint.uri_segments() {
+			Ok(_) => endpoint.path_params(),
+			Err(message) => {
+				self.error(format!("endpoints.{name}.uri"), message);
+				return;
+			}
+		};
+
+		// The router only allows one parameter per segment
+		if let Ok(segments) = UriSegment::parse(endpoint.path()){
+			for (i,segment) in segments.iter().enumerate(){
+				let UriSegment::Param(param) = segment else {
+					continue;
+				};
+				let starts_segment = matches!(
+					i.checked_sub(1).map(|i|&segments[i]),
+					Some(UriSegment::Literal(text)) if text.ends_with('/')
+				);
+				let ends_segment = match segments.get(i + 1) {
+					Some(UriSegment::Literal(text)) => text.starts_with('/'),
+					Some(UriSegment::Param(_)) => false,
+					None => true
+				};
+				if !starts_segment || !ends_segment{
+					self.error(
+						format!("endpoints.{name}.uri"), 
+						format!("path parameter `{}` must be a whole segment of the path",param)
+					);
+				}
+			}
+		}
+
+		let mut seen = HashSet::new();
+		for param in &params{
+			if !seen.insert(param){
+				self.error(format!("endpoints.{name}.uri"), format!("duplicate path parameter `{}`",param));
+			}
+			if !endpoint.params.contains_key(*param){
+				self.error(
+					format!("endpoints.{name}.uri"), 
+					format!("path parameter `{}` is missing from params",param)
+				);
+			}
+		}
+
+		if !params.is_empty(){
+			self.check_generated_type(format!("endpoints.{name}.params"), "path", &path_type_name(name));
+		}
+
+		for (param,_type) in &endpoint.params{
+			let path = format!("endpoints.{name}.params.{param}");
+			self.check_identifier(path.clone(), param);
+			let local = [Convention::Rust.member(param),Convention::Typescript.member(param)]
+				.into_iter()
+				.find(|local|CLIENT_METHOD_LOCALS.contains(&local.as_str()));
+			if let Some(local) = local{
+				self.error(path.clone(), format!("`{}` conflicts with the client method's `{}` variable",param,local));
+			}
+			if !params.contains(&param.as_str()){
+				self.error(path.clone(), format!("parameter `{}` is not used in the uri",param));
+			}
+			if !_type.is_primitive(){
+				self.error(path, format!("path parameters must be a primitive type, found `{}`",_type));
+			}
+		}
+	}
+
+	/// Types that contain themselves without a list or map in between
+	/// would have an infinite size.
+	fn check_cycles(&mut self){
+		let schema = self.schema;
+		let mut graph: HashMap<&str,Vec<&str>> = HashMap::new();
+
+		for (name,fields) in &schema.structs{
+			let edges = graph.entry(name).or_default();
+			for _type in fields.values(){
+				direct_references(_type, edges);
+			}
+		}
+
+		for (name,value) in &schema.enums{
+			let edges = graph.entry(name).or_default();
+			if let Enum::Tagged(union) = value{
+				for _type in union.variants.values().flat_map(|fields|fields.values()){
+					direct_references(_type, edges);
+				}
+			}
+		}
+
+		let mut names: Vec<&str> = graph.keys().copied().collect();
+		names.sort();
+
+		let mut visited = HashSet::new();
+		for name in names{
+			let mut stack = vec![];
+			self.visit(name, &graph, &mut visited, &mut stack);
+		}
+	}
+
+	fn visit<'a>(
+		&mut self,
+		name: &'a str,
+		graph: &HashMap<&'a str,Vec<&'a str>>,
+		visited: &mut HashSet<&'a str>,
+		stack: &mut Vec<&'a str>,
+	){
+		if let Some(start) = stack.iter().position(|item|*item == name){
+			let mut cycle = stack[start..].to_vec();
+			cycle.push(name);
+
+			let section = if self.schema.enums.contains_key(name) {"enums"} else {"structs"};
+			self.error(
+				format!("{section}.{name}"),
+				format!(
+					"`{}` contains itself without indirection ({}), use a list or map",
+					name,
+					cycle.join(" -> ")
+				)
+			);
+			return;
+		}
+
+		if !visited.insert(name){
+			return;
+		}
+
+		stack.push(name);
+		for next in graph.get(name).into_iter().flatten(){
+			self.visit(next, graph, visited, stack);
+		}
+		stack.pop();
+	}
+}
+
+/// Collect the types that are stored inline, lists and maps are heap
+/// allocated so they break cycles.
+fn direct_references<'a>(_type: &'a SchemaType, references: &mut Vec<&'a str>){
+	match _type {
+		SchemaType::Optional(inner) => direct_references(inner, references),
+		SchemaType::Struct(name) => references.push(name),
+		_ => {}
+	}
+}
+
+/// The path with its parameters left unnamed, `/users/{id}` becomes
+/// `/users/{}`.
+fn route_shape(path: &str) -> String{
+	let Ok(segments) = UriSegment::parse(path) else {
+		return String::from(path);
+	};
+	segments.into_iter()
+		.map(|segment|match segment {
+			UriSegment::Literal(text) => text,
+			UriSegment::Param(_) => "{}"
+		})
+		.collect()
+}
+
+/// Base urls are absolute, e.g. `https://api.example.com/v1`.
+fn is_base_url(url: &str) -> bool{
+	let Some((scheme,rest)) = url.split_once("://") else {
+		return false;
+	};
+	matches!(scheme,"http" | "https")
+		&& !rest.is_empty()
+		&& !rest.starts_with('/')
+		&& !url.chars().any(|c|c.is_whitespace() || c.is_control() || "{}?#".contains(c))
+}
+
+/// Header names are http tokens, e.g. `Api-Schema-Checksum`.
+fn is_header_name(name: &str) -> bool{
+	!name.is_empty() && name.chars().all(|c|{
+		c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+	})
+}
+
+fn check_uri(uri: &str) -> Result<(),String>{
+	if uri.is_empty(){
+		return Err(String::from("uri cannot be empty"));
+	}
+
+	if uri.chars().any(|c|c.is_whitespace() || c.is_control()){
+		return Err(format!("uri `{}` contains whitespace",uri));
+	}
+
+	if uri.starts_with('/'){
+		return Ok(());
+	}
+
+	let Some((scheme,rest)) = uri.split_once("://") else {
+		return Err(format!("uri `{}` must be absolute or start with `/`",uri));
+	};
+
+	if scheme != "http" && scheme != "https"{
+		return Err(format!("uri `{}` has an unsupported scheme `{}`",uri,scheme));
+	}
+
+	let host = rest.split(['/','?','#']).next().unwrap_or_default();
+	if host.is_empty(){
+		return Err(format!("uri `{}` is missing a host",uri));
+	}
+
+	Ok(())
+}
+
+/// Find the location of a key in a block style yaml document.
+///
+/// If the full path cannot be found the location of the deepest key
+/// that was found is returned instead, along with the number of keys 
+/// that matched.
+fn locate(source: &str, path: &[&str]) -> Option<(usize,Location)>{
+	let mut found = None;
+	let mut depth = 0;
+	// The indentation of the last matched key
+	let mut parent_indent: Option<usize> = None;
+	// The indentation of the children of the last matched key
+	let mut child_indent: Option<usize> = None;
+
+	for (index,line) in source.lines().enumerate(){
+		let content = line.trim_start_matches(' ');
+		if content.is_empty() || content.starts_with('#') || content.starts_with("---"){
+			continue;
+		}
+
+		let indent = line.len() - content.len();
+		if parent_indent.is_some_and(|parent| indent <= parent){
+			break;
+		}
+
+		if indent != *child_indent.get_or_insert(indent){
+			continue;
+		}
+
+		let Some((key,_)) = content.split_once(':') else {
+			continue;
+		};
+		let key = key.trim().trim_matches(['"','\'']);
+
+		if key == path[depth]{
+			depth += 1;
+			found = Some((depth,Location{line: index + 1, column: indent + 1}));
+			if depth == path.len(){
+				break;
+			}
+			parent_indent = Some(indent);
+			child_indent = None;
+		}
+	}
+
+	found
+}
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	fn diagnostics(source: &str) -> Vec<Diagnostic>{
+		let schema = ApiSchema::parse(source).unwrap();
+		match validate(&schema, Some(source)) {
+			Ok(_) => vec![],
+			Err(error) => error.diagnostics
+		}
+	}
+
+	#[test]
+	fn valid_schema(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    id: int\n",
+			"    friends: User[]\n",
+			"    status: Status\n",
+			"enums:\n",
+			"  Status:\n",
+			"    - active\n",
+			"endpoints:\n",
+			"  get_user:\n",
+			"    uri: https://example.com/user\n",
+			"    method: GET\n",
+			"    returns: User\n",
+			"  delete_user:\n",
+			"    uri: https://example.com/user\n",
+			"    method: DELETE\n",
+		);
+
+		assert!(diagnostics(source).is_empty());
+	}
+
+	#[test]
+	fn unknown_type(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    id: int\n",
+			"    manager: Usr\n",
+			"endpoints: {}\n",
+		);
+
+		assert_eq!(
+			diagnostics(source),
+			vec![Diagnostic{
+				path: "structs.User.manager".to_string(),
+				message: "unknown type `Usr`".to_string(),
+				file: None,
+				location: Some(Location{line: 6, column: 5})
+			}]
+		);
+	}
+
+	#[test]
+	fn reports_every_error(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  string:\n",
+			"    type: int\n",
+			"endpoints:\n",
+			"  get-user:\n",
+			"    uri: example.com\n",
+			"    method: POST\n",
+			"    input: Payload\n",
+			"    returns: int\n",
+		);
+
+		let mut paths: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|diagnostic.path)
+			.collect();
+		paths.sort();
+
+		assert_eq!(paths,vec![
+			"endpoints.get-user",
+			"endpoints.get-user.input",
+			"endpoints.get-user.uri",
+			"structs.string",
+			"structs.string.type",
+		]);
+	}
+
+	#[test]
+	fn generated_type_names(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  Client:\n",
+			"    id: int\n",
+			"enums:\n",
+			"  Api:\n",
+			"    - rest\n",
+			"endpoints: {}\n",
+		);
+
+		let mut paths: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|diagnostic.path)
+			.collect();
+		paths.sort();
+
+		assert_eq!(paths,vec!["enums.Api","structs.Client"]);
+	}
+
+	#[test]
+	fn typescript_global_names(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  Record:\n",
+			"    id: int\n",
+			"  promise:\n",
+			"    id: int\n",
+			"endpoints: {}\n",
+		);
+
+		let mut messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|format!("{}: {}",diagnostic.path,diagnostic.message))
+			.collect();
+		messages.sort();
+
+		assert_eq!(messages,vec![
+			"structs.Record: `Record` is a reserved type name",
+			"structs.promise: `promise` becomes the reserved type name `Promise`",
+		]);
+	}
+
+	#[test]
+	fn runtime_validator_names(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  one_of:\n",
+			"    id: int\n",
+			"  Validator:\n",
+			"    id: int\n",
+			"enums:\n",
+			"  Optional:\n",
+			"    - rest\n",
+			"endpoints: {}\n",
+		);
+
+		let mut messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|format!("{}: {}",diagnostic.path,diagnostic.message))
+			.collect();
+		messages.sort();
+
+		assert_eq!(messages,vec![
+			"enums.Optional: the validator `validateOptional` conflicts with a generated function",
+			"structs.Validator: `Validator` is a reserved type name",
+			"structs.one_of: the validator `validateOneOf` conflicts with a generated function",
+		]);
+	}
+
+	#[test]
+	fn client_method_names(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs: {}\n",
+			"endpoints:\n",
+			"  new:\n",
+			"    uri: /users\n",
+			"    method: POST\n",
+			"  withClient:\n",
+			"    uri: /clients\n",
+			"    method: GET\n",
+			"  base_url:\n",
+			"    uri: /urls\n",
+			"    method: GET\n",
+			"  headers:\n",
+			"    uri: /headers\n",
+			"    method: GET\n",
+		);
+
+		let messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|diagnostic.message)
+			.collect();
+		assert_eq!(messages,vec![
+			"`new` conflicts with the rust client's `new` method",
+			"`withClient` conflicts with the rust client's `with_client` method",
+			"`base_url` conflicts with the typescript client's `baseUrl` member",
+			"`headers` conflicts with the typescript client's `headers` member",
+		]);
+	}
+
+	#[test]
+	fn endpoint_errors(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  GetUserError:\n",
+			"    message: string\n",
+			"endpoints:\n",
+			"  get_user:\n",
+			"    uri: https://example.com/users\n",
+			"    method: GET\n",
+			"    errors:\n",
+			"      200: GetUserError\n",
+			"      404: NotFound\n",
+			"  head_user:\n",
+			"    uri: https://example.com/users\n",
+			"    method: HEAD\n",
+			"    errors:\n",
+			"      404: string\n",
+		);
+
+		let mut paths: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|diagnostic.path)
+			.collect();
+		paths.sort();
+
+		assert_eq!(paths,vec![
+			"endpoints.get_user.errors",
+			"endpoints.get_user.errors.200",
+			"endpoints.get_user.errors.404",
+			"endpoints.head_user.errors.404",
+		]);
+	}
+
+	#[test]
+	fn generated_endpoint_types(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  list_orders_query:\n",
+			"    page: int\n",
+			"  GetOrderPath:\n",
+			"    id: int\n",
+			"endpoints:\n",
+			"  list_orders:\n",
+			"    uri: /orders\n",
+			"    method: GET\n",
+			"    query:\n",
+			"      page: int?\n",
+			"  get_order:\n",
+			"    uri: /orders/{id}\n",
+			"    method: GET\n",
+			"    params:\n",
+			"      id: int\n",
+		);
+
+		let diagnostics = diagnostics(source);
+		let paths: Vec<&str> = diagnostics.iter().map(|diagnostic|diagnostic.path.as_str()).collect();
+		assert_eq!(paths,vec!["endpoints.list_orders.query","endpoints.get_order.params"]);
+		assert_eq!(
+			diagnostics[0].message,
+			"the generated query type `ListOrdersQuery` conflicts with an existing type"
+		);
+	}
+
+	#[test]
+	fn codegen_options(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    id: int\n",
+			"endpoints: {}\n",
+			"codegen:\n",
+			"  rust:\n",
+			"    derives: [Eq, serde::Serialize, Hash()]\n",
+			"    structs:\n",
+			"      User:\n",
+			"        derives: [Hash]\n",
+			"      Usr:\n",
+			"        derives: [Hash]\n",
+			"        attributes:\n",
+			"          - serde(rename_all = \"camelCase\")\n",
+		);
+
+		let messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|format!("{}: {}",diagnostic.path,diagnostic.message))
+			.collect();
+
+		assert_eq!(messages,vec![
+			"codegen.rust.derives: `Hash()` is not a valid derive",
+			"codegen.rust.structs.Usr: unknown struct `Usr`",
+			"codegen.rust.structs.Usr.attributes: `serde(rename_all = \"camelCase\")` would change the names sent on the wire",
+		]);
+	}
+
+	#[test]
+	fn codegen_clients(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    id: int\n",
+			"endpoints: {}\n",
+			"codegen:\n",
+			"  client_name: User\n",
+			"  base_url: example.com/api\n",
+			"  rust:\n",
+			"    client_name: Rejection\n",
+			"  typescript:\n",
+			"    client_name: string\n",
+			"    output: ''\n",
+		);
+
+		let messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|format!("{}: {}",diagnostic.path,diagnostic.message))
+			.collect();
+
+		assert_eq!(messages,vec![
+			"codegen.client_name: `User` conflicts with an existing type",
+			"codegen.rust.client_name: `Rejection` is a reserved type name",
+			"codegen.typescript.client_name: `string` is a reserved type name",
+			"codegen.base_url: `example.com/api` is not an absolute http or https url",
+			"codegen.typescript.output: output cannot be empty",
+		]);
+		assert!(is_base_url("https://api.example.com/v1"));
+		assert!(is_base_url("http://localhost:8080"));
+	}
+
+	#[test]
+	fn checksum_header(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"checksum_header: Schema Checksum\n",
+			"structs: {}\n",
+			"endpoints: {}\n",
+		);
+
+		assert_eq!(
+			diagnostics(source),
+			vec![Diagnostic{
+				path: "checksum_header".to_string(),
+				message: "`Schema Checksum` is not a valid header name".to_string(),
+				file: None,
+				location: Some(Location{line: 3, column: 1})
+			}]
+		);
+	}
+
+	#[test]
+	fn version_header(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"checksum_header: X-Schema\n",
+			"version_header: x-schema\n",
+			"structs: {}\n",
+			"endpoints: {}\n",
+		);
+
+		assert_eq!(
+			diagnostics(source),
+			vec![Diagnostic{
+				path: "version_header".to_string(),
+				message: "the checksum and version are both sent in `x-schema`".to_string(),
+				file: None,
+				location: Some(Location{line: 4, column: 1})
+			}]
+		);
+	}
+
+	#[test]
+	fn direct_cycle(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  A:\n",
+			"    b: B?\n",
+			"  B:\n",
+			"    a: A\n",
+			"  Tree:\n",
+			"    children: Tree[]\n",
+			"endpoints: {}\n",
+		);
+
+		let diagnostics = diagnostics(source);
+		assert_eq!(diagnostics.len(),1);
+		assert_eq!(diagnostics[0].path,"structs.A");
+		assert!(diagnostics[0].message.contains("A -> B -> A"));
+	}
+
+	#[test]
+	fn struct_and_enum_collision(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  Status:\n",
+			"    id: int\n",
+			"enums:\n",
+			"  Status:\n",
+			"    - active\n",
+			"    - active\n",
+			"endpoints: {}\n",
+		);
+
+		let diagnostics = diagnostics(source);
+		assert_eq!(diagnostics.len(),2);
+		assert!(diagnostics.iter().all(|diagnostic|diagnostic.path == "enums.Status"));
+		assert_eq!(diagnostics[0].location,Some(Location{line: 7,column: 3}));
+	}
+
+	#[test]
+	fn naming_conventions(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  user_profile:\n",
+			"    created_at: string\n",
+			"    createdAt: string\n",
+			"    Type: int\n",
+			"  UserProfile:\n",
+			"    id: int\n",
+			"enums:\n",
+			"  Status:\n",
+			"    - active\n",
+			"    - Active\n",
+			"endpoints:\n",
+			"  get_user:\n",
+			"    uri: https://example.com/users\n",
+			"    method: GET\n",
+			"  getUser:\n",
+			"    uri: https://example.com/user\n",
+			"    method: GET\n",
+		);
+
+		let mut messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|format!("{}: {}",diagnostic.path,diagnostic.message))
+			.collect();
+		messages.sort();
+
+		assert_eq!(messages,vec![
+			"endpoints.get_user: `getUser` and `get_user` are both `getUser` in typescript",
+			"endpoints.get_user: `getUser` and `get_user` are both `get_user` in rust",
+			"enums.Status: `Active` and `active` are both `Active` in rust",
+			"structs.user_profile.Type: `Type` becomes `type` in rust, which is not a valid identifier",
+			"structs.user_profile.created_at: `createdAt` and `created_at` are both `createdAt` in typescript",
+			"structs.user_profile.created_at: `createdAt` and `created_at` are both `created_at` in rust",
+			"structs.user_profile: `UserProfile` and `user_profile` are both `UserProfile`",
+		]);
+	}
+
+	#[test]
+	fn locate_in_included_file(){
+		let root = SchemaFile{
+			path: PathBuf::from("schema.yml"),
+			contents: String::from(concat!(
+				"version: 0.1.0\n",
+				"schema_diff: none\n",
+				"include: [users.yml]\n",
+				"structs:\n",
+				"  Order:\n",
+				"    user: User\n",
+				"endpoints: {}\n",
+			))
+		};
+		let users = SchemaFile{
+			path: PathBuf::from("users.yml"),
+			contents: String::from("structs:\n  User:\n    id: int\n    manager: Usr\n")
+		};
+
+		let mut schema = ApiSchema::parse(&root.contents).unwrap();
+		let included = ApiSchema::parse(&format!("version: 0.1.0\nschema_diff: none\n{}endpoints: {{}}\n",users.contents)).unwrap();
+		schema.structs.extend(included.structs);
+
+		let error = validate_files(&schema, &[root,users]).unwrap_err();
+		assert_eq!(error.diagnostics.len(),1);
+		assert_eq!(error.diagnostics[0].file,Some(PathBuf::from("users.yml")));
+		assert_eq!(error.diagnostics[0].location,Some(Location{line: 4, column: 5}));
+	}
+
+	#[test]
+	fn path_params(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs: {}\n",
+			"endpoints:\n",
+			"  get_order:\n",
+			"    uri: /users/{id}/orders/{order_id}\n",
+			"    method: GET\n",
+			"    params:\n",
+			"      id: int\n",
+			"      user: int[]\n",
+			"    returns: int\n",
+		);
+
+		let mut messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|diagnostic.to_string())
+			.collect();
+		messages.sort();
+
+		assert_eq!(messages,vec![
+			"endpoints.get_order.params.user: parameter `user` is not used in the uri at line 10 column 7",
+			"endpoints.get_order.params.user: path parameters must be a primitive type, found `int[]` at line 10 column 7",
+			"endpoints.get_order.uri: path parameter `order_id` is missing from params at line 6 column 5",
+		]);
+	}
+
+	#[test]
+	fn partial_path_segments(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs: {}\n",
+			"endpoints:\n",
+			"  get_file:\n",
+			"    uri: https://example.com/files/{name}.json\n",
+			"    method: GET\n",
+			"    params:\n",
+			"      name: string\n",
+			"  get_pair:\n",
+			"    uri: /pairs/{a}-{b}\n",
+			"    method: GET\n",
+			"    params:\n",
+			"      a: int\n",
+			"      b: int\n",
+			"  get_version:\n",
+			"    uri: /v{version}/{id}\n",
+			"    method: GET\n",
+			"    params:\n",
+			"      version: int\n",
+			"      id: int\n",
+		);
+
+		let messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|format!("{}: {}",diagnostic.path,diagnostic.message))
+			.collect();
+
+		assert_eq!(messages,vec![
+			"endpoints.get_file.uri: path parameter `name` must be a whole segment of the path",
+			"endpoints.get_pair.uri: path parameter `a` must be a whole segment of the path",
+			"endpoints.get_pair.uri: path parameter `b` must be a whole segment of the path",
+			"endpoints.get_version.uri: path parameter `version` must be a whole segment of the path",
+		]);
+	}
+
+	#[test]
+	fn client_method_locals(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  User:\n",
+			"    id: int\n",
+			"endpoints:\n",
+			"  get_thing:\n",
+			"    uri: https://example.com/things/{url}/{payload}/{is_rejection}/{id}\n",
+			"    method: POST\n",
+			"    params:\n",
+			"      url: string\n",
+			"      payload: int\n",
+			"      is_rejection: boolean\n",
+			"      id: int\n",
+			"    input: User\n",
+		);
+
+		let messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|format!("{}: {}",diagnostic.path,diagnostic.message))
+			.collect();
+
+		assert_eq!(messages,vec![
+			"endpoints.get_thing.params.url: `url` conflicts with the client method's `url` variable",
+			"endpoints.get_thing.params.payload: `payload` conflicts with the client method's `payload` variable",
+			"endpoints.get_thing.params.is_rejection: `is_rejection` conflicts with the client method's `isRejection` variable",
+		]);
+	}
+
+	#[test]
+	fn query_params(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  Filter:\n",
+			"    id: int\n",
+			"endpoints:\n",
+			"  list_orders:\n",
+			"    uri: /orders\n",
+			"    method: GET\n",
+			"    query:\n",
+			"      page: int?\n",
+			"      tags: string[]\n",
+			"      filter: Filter\n",
+			"      ids: int[]?\n",
+			"    input: int\n",
+			"    returns: int\n",
+		);
+
+		let mut paths: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|diagnostic.path)
+			.collect();
+		paths.sort();
+
+		assert_eq!(paths,vec![
+			"endpoints.list_orders.input",
+			"endpoints.list_orders.query.filter",
+			"endpoints.list_orders.query.ids",
+		]);
+	}
+
+	#[test]
+	fn body_less_methods(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs: {}\n",
+			"endpoints:\n",
+			"  health:\n",
+			"    uri: /health\n",
+			"    method: HEAD\n",
+			"    input: int\n",
+			"    returns: int\n",
+			"  upsert:\n",
+			"    uri: /user\n",
+			"    method: PUT\n",
+			"    input: int\n",
+			"    returns: int\n",
+		);
+
+		let mut messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|diagnostic.message)
+			.collect();
+		messages.sort();
+
+		assert_eq!(messages,vec![
+			"HEAD requests cannot have a body, use query parameters instead",
+			"HEAD responses cannot have a body",
+		]);
+	}
+
+	#[test]
+	fn route_conflict(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs: {}\n",
+			"endpoints:\n",
+			"  get_user:\n",
+			"    uri: https://example.com/user\n",
+			"    method: GET\n",
+			"  fetch_user:\n",
+			"    uri: /user\n",
+			"    method: GET\n",
+			"  create_user:\n",
+			"    uri: /user\n",
+			"    method: POST\n",
+		);
+
+		let diagnostics = diagnostics(source);
+		assert_eq!(diagnostics.len(),1);
+		assert_eq!(diagnostics[0].message,"`fetch_user` and `get_user` are both GET /user");
+	}
+
+	#[test]
+	fn route_param_names(){
+		let source = concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs: {}\n",
+			"endpoints:\n",
+			"  get_user:\n",
+			"    uri: /users/{id}\n",
+			"    method: GET\n",
+			"    params:\n",
+			"      id: int\n",
+			"  update_user:\n",
+			"    uri: /users/{user_id}\n",
+			"    method: POST\n",
+			"    params:\n",
+			"      user_id: int\n",
+			"  find_user:\n",
+			"    uri: /users/{name}\n",
+			"    method: GET\n",
+			"    params:\n",
+			"      name: string\n",
+		);
+
+		let messages: Vec<String> = diagnostics(source)
+			.into_iter()
+			.map(|diagnostic|diagnostic.message)
+			.collect();
+		assert_eq!(messages,vec![
+			"`find_user` and `get_user` are both GET /users/{id}",
+			"`find_user` and `get_user` name the parameters of the same path differently, `/users/{name}` and `/users/{id}`",
+			"`find_user` and `update_user` name the parameters of the same path differently, `/users/{name}` and `/users/{user_id}`",
+		]);
+	}
+
+	#[test]
+	fn uri_validation(){
+		assert!(check_uri("https://example.com/user").is_ok());
+		assert!(check_uri("/user").is_ok());
+		assert!(check_uri("example.com/user").is_err());
+		assert!(check_uri("ftp://example.com").is_err());
+		assert!(check_uri("https:///user").is_err());
+		assert!(check_uri("/user name").is_err());
+	}
+}
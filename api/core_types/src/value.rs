@@ -0,0 +1,246 @@
+//! Checking json values against the types in a schema.
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::{ApiSchema, Enum, SchemaType};
+
+/// A value that doesn't match its type.
+#[derive(Debug,Clone,PartialEq,Eq,Serialize,Deserialize)]
+pub struct FieldError{
+	/// The path to the value, e.g. `$.items[0].id`
+	pub path: String,
+	pub message: String
+}
+
+/// Check that the value matches the type, returning an error for every
+/// value that doesn't.
+///
+/// Unknown fields are allowed and missing optional fields are treated
+/// as `null`.
+///
+/// ```
+/// use core_types::{ApiSchema,SchemaType,value::check_value};
+///
+/// let schema = ApiSchema::parse("version: 0.1.0\nschema_diff: none\nstructs:\n  User:\n    id: int\nendpoints: {}").unwrap();
+/// let errors = check_value(&schema, &SchemaType::Struct("User".to_string()), &serde_json::json!({"id":"1"}));
+/// assert_eq!(errors[0].path,"$.id");
+/// assert_eq!(errors[0].message,"expected int, found string");
+/// ```
+pub fn check_value(schema: &ApiSchema, _type: &SchemaType, value: &Value) -> Vec<FieldError>{
+	let mut checker = Checker{
+		schema,
+		errors: vec![]
+	};
+	checker.check(String::from("$"), _type, value);
+	checker.errors
+}
+
+struct Checker<'a>{
+	schema: &'a ApiSchema,
+	errors: Vec<FieldError>
+}
+
+impl Checker<'_>{
+	fn error(&mut self, path: String, message: String){
+		self.errors.push(FieldError { path, message });
+	}
+
+	fn mismatch(&mut self, path: String, expected: &str, value: &Value){
+		self.error(path, format!("expected {}, found {}",expected,kind(value)));
+	}
+
+	fn check(&mut self, path: String, _type: &SchemaType, value: &Value){
+		match _type {
+			SchemaType::Int => {
+				if !(value.is_i64() || value.is_u64()){
+					self.mismatch(path, "int", value);
+				} else if value.as_i64().and_then(|value|i32::try_from(value).ok()).is_none(){
+					self.error(path, format!("{} is out of range for an int",value));
+				}
+			},
+			SchemaType::Float => {
+				if !value.is_number(){
+					self.mismatch(path, "float", value);
+				}
+			},
+			SchemaType::String => {
+				if !value.is_string(){
+					self.mismatch(path, "string", value);
+				}
+			},
+			SchemaType::Boolean => {
+				if !value.is_boolean(){
+					self.mismatch(path, "boolean", value);
+				}
+			},
+			SchemaType::Optional(inner) => {
+				if !value.is_null(){
+					self.check(path, inner, value);
+				}
+			},
+			SchemaType::Array(inner) => {
+				let Some(values) = value.as_array() else {
+					return self.mismatch(path, "array", value);
+				};
+				for (i,value) in values.iter().enumerate(){
+					self.check(format!("{path}[{i}]"), inner, value);
+				}
+			},
+			SchemaType::Map(inner) => {
+				let Some(values) = value.as_object() else {
+					return self.mismatch(path, "map", value);
+				};
+				for (key,value) in values{
+					self.check(format!("{path}.{key}"), inner, value);
+				}
+			},
+			SchemaType::Struct(name) => self.check_named(path, name, value)
+		}
+	}
+
+	fn check_named(&mut self, path: String, name: &str, value: &Value){
+		let schema = self.schema;
+		if let Some(fields) = schema.structs.get(name){
+			if !value.is_object(){
+				return self.mismatch(path, name, value);
+			}
+			self.check_fields(&path, fields.iter(), value);
+			return;
+		}
+
+		match schema.enums.get(name) {
+			Some(Enum::Simple(values)) => {
+				let is_value = value.as_str()
+					.is_some_and(|value|values.iter().any(|variant|variant == value));
+				if !is_value{
+					let values: Vec<String> = values.iter().map(|value|format!("\"{value}\"")).collect();
+					self.error(path, format!("expected one of {}, found {}",values.join(", "),value));
+				}
+			},
+			Some(Enum::Tagged(union)) => {
+				if !value.is_object(){
+					return self.mismatch(path, name, value);
+				}
+				let tag = value.get(&union.tag).and_then(Value::as_str);
+				let Some(fields) = tag.and_then(|tag|union.variants.get(tag)) else {
+					let variants: Vec<String> = union.variants.keys().map(|variant|format!("\"{variant}\"")).collect();
+					return self.error(
+						format!("{path}.{}",union.tag),
+						format!("expected one of {}",variants.join(", "))
+					);
+				};
+				self.check_fields(&path, fields.iter(), value);
+			},
+			// Unknown types are caught when the schema is validated
+			None => {}
+		}
+	}
+
+	fn check_fields<'a>(
+		&mut self,
+		path: &str,
+		fields: impl Iterator<Item = (&'a String,&'a SchemaType)>,
+		value: &Value
+	){
+		for (name,_type) in fields{
+			let path = format!("{path}.{name}");
+			match value.get(name) {
+				Some(value) => self.check(path, _type, value),
+				None if _type.is_optional() => {},
+				None => self.error(path, String::from("missing field"))
+			}
+		}
+	}
+}
+
+/// The json type of the value.
+fn kind(value: &Value) -> &'static str{
+	match value {
+		Value::Null => "null",
+		Value::Bool(_) => "boolean",
+		Value::Number(number) if number.is_f64() => "float",
+		Value::Number(_) => "int",
+		Value::String(_) => "string",
+		Value::Array(_) => "array",
+		Value::Object(_) => "object"
+	}
+}
+
+#[cfg(test)]
+mod tests{
+	use serde_json::json;
+	use super::*;
+
+	fn schema() -> ApiSchema{
+		ApiSchema::parse(concat!(
+			"version: 0.1.0\n",
+			"schema_diff: none\n",
+			"structs:\n",
+			"  Order:\n",
+			"    id: int\n",
+			"    note: string?\n",
+			"    items: Item[]\n",
+			"    prices: map<string, float>\n",
+			"    status: Status\n",
+			"  Item:\n",
+			"    name: string\n",
+			"    shape: Shape\n",
+			"enums:\n",
+			"  Status:\n",
+			"    - pending\n",
+			"    - paid\n",
+			"  Shape:\n",
+			"    tag: kind\n",
+			"    variants:\n",
+			"      Circle:\n",
+			"        radius: float\n",
+			"      Point: {}\n",
+			"endpoints: {}\n",
+		)).unwrap()
+	}
+
+	fn errors(value: Value) -> Vec<(String,String)>{
+		check_value(&schema(), &SchemaType::Struct(String::from("Order")), &value)
+			.into_iter()
+			.map(|error|(error.path,error.message))
+			.collect()
+	}
+
+	#[test]
+	fn valid_value(){
+		let value = json!({
+			"id": 1,
+			"items": [{"name": "ball", "shape": {"kind": "Circle", "radius": 2}}],
+			"prices": {"ball": 2.5},
+			"status": "paid",
+			"extra": true
+		});
+
+		assert_eq!(errors(value),vec![]);
+	}
+
+	#[test]
+	fn invalid_value(){
+		let value = json!({
+			"id": 2147483648u64,
+			"note": 5,
+			"items": [{"name": "ball", "shape": {"kind": "Square"}}, {"shape": {"kind": "Circle"}}],
+			"prices": {"ball": "free"},
+			"status": "lost"
+		});
+
+		assert_eq!(errors(value),vec![
+			("$.id".to_string(),"2147483648 is out of range for an int".to_string()),
+			("$.note".to_string(),"expected string, found int".to_string()),
+			("$.items[0].shape.kind".to_string(),"expected one of \"Circle\", \"Point\"".to_string()),
+			("$.items[1].name".to_string(),"missing field".to_string()),
+			("$.items[1].shape.radius".to_string(),"missing field".to_string()),
+			("$.prices.ball".to_string(),"expected float, found string".to_string()),
+			("$.status".to_string(),"expected one of \"pending\", \"paid\", found \"lost\"".to_string()),
+		]);
+	}
+
+	#[test]
+	fn wrong_root_type(){
+		assert_eq!(errors(json!([])),vec![("$".to_string(),"expected Order, found array".to_string())]);
+	}
+}
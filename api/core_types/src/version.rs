@@ -0,0 +1,174 @@
+//! Schema versions and the allowed difference between a client's and a
+//! server's version.
+use std::{fmt::Display, str::FromStr};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The header that clients send the schema version in, unless the 
+/// schema sets its own `version_header`.
+pub const VERSION_HEADER: &str = "Api-Schema-Version";
+
+/// A semantic version, e.g. `1.2.0`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash,Default)]
+pub struct Version{
+	pub major: u64,
+	pub minor: u64,
+	pub patch: u64
+}
+
+impl Version{
+	pub fn new(major: u64, minor: u64, patch: u64) -> Self{
+		Self { major, minor, patch }
+	}
+
+	/// The largest part of the version that differs between the two
+	/// versions.
+	///
+	/// ```
+	/// use core_types::{Version,SchemaDiff};
+	///
+	/// let version = Version::new(1,2,0);
+	/// assert_eq!(version.diff(&Version::new(1,2,5)),SchemaDiff::Patch);
+	/// assert_eq!(version.diff(&Version::new(1,0,0)),SchemaDiff::Minor);
+	/// ```
+	pub fn diff(&self, other: &Version) -> SchemaDiff{
+		if self.major != other.major{
+			SchemaDiff::Major
+		} else if self.minor != other.minor{
+			SchemaDiff::Minor
+		} else if self.patch != other.patch{
+			SchemaDiff::Patch
+		} else {
+			SchemaDiff::None
+		}
+	}
+}
+
+impl Display for Version{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f,"{}.{}.{}",self.major,self.minor,self.patch)
+	}
+}
+
+/// A version that is not in the `major.minor.patch` format.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ParseVersionError(String);
+
+impl Display for ParseVersionError{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f,"`{}` is not a valid version, expected major.minor.patch",self.0)
+	}
+}
+
+impl std::error::Error for ParseVersionError{}
+
+impl FromStr for Version{
+	type Err = ParseVersionError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let error = ||ParseVersionError(String::from(s));
+		let parts = s.trim()
+			.split('.')
+			.map(|part|{
+				// Leading zeros and signs are not allowed
+				let is_number = !part.is_empty()
+					&& part.chars().all(|c|c.is_ascii_digit())
+					&& (part == "0" || !part.starts_with('0'));
+				is_number.then(||part.parse::<u64>().ok()).flatten()
+			})
+			.collect::<Option<Vec<_>>>()
+			.ok_or_else(error)?;
+
+		match parts.as_slice() {
+			[major,minor,patch] => Ok(Self::new(*major, *minor, *patch)),
+			_ => Err(error())
+		}
+	}
+}
+
+impl Serialize for Version{
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where S: Serializer
+	{
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for Version{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where D: Deserializer<'de>
+	{
+		let value = String::deserialize(deserializer)?;
+		value.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+/// The largest difference between the client's and the server's schema
+/// version that the server accepts.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,PartialOrd,Ord,Hash,Default,Serialize,Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaDiff{
+	/// Only clients with the same checksum or the same version are accepted
+	#[default]
+	None,
+	Patch,
+	Minor,
+	Major
+}
+
+impl SchemaDiff{
+	/// Returns `true` if the client's version is not newer than the 
+	/// server's and the difference is within the allowed difference. 
+	/// Newer clients could call endpoints the server doesn't have.
+	pub fn allows(&self, server: &Version, client: &Version) -> bool{
+		client <= server && server.diff(client) <= *self
+	}
+}
+
+impl Display for SchemaDiff{
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			Self::None => write!(f,"none"),
+			Self::Patch => write!(f,"patch"),
+			Self::Minor => write!(f,"minor"),
+			Self::Major => write!(f,"major"),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests{
+	use super::*;
+
+	#[test]
+	fn parse_version(){
+		assert_eq!("1.20.3".parse(),Ok(Version::new(1, 20, 3)));
+		assert_eq!(Version::new(0, 1, 0).to_string(),"0.1.0");
+
+		for version in ["1.2","1.2.3.4","1.02.3","v1.2.3","1.-2.3",""]{
+			assert!(version.parse::<Version>().is_err(),"{version} should be invalid");
+		}
+	}
+
+	#[test]
+	fn schema_diff_allows(){
+		let server = Version::new(1, 2, 3);
+
+		assert!(SchemaDiff::None.allows(&server, &Version::new(1, 2, 3)));
+		assert!(!SchemaDiff::None.allows(&server, &Version::new(1, 2, 4)));
+		assert!(!SchemaDiff::Patch.allows(&server, &Version::new(1, 2, 4)));
+		assert!(!SchemaDiff::Minor.allows(&server, &Version::new(1, 3, 0)));
+		assert!(!SchemaDiff::Major.allows(&server, &Version::new(2, 0, 0)));
+		assert!(SchemaDiff::Patch.allows(&server, &Version::new(1, 2, 0)));
+		assert!(!SchemaDiff::Patch.allows(&server, &Version::new(1, 3, 3)));
+		assert!(SchemaDiff::Minor.allows(&server, &Version::new(1, 0, 9)));
+		assert!(!SchemaDiff::Minor.allows(&server, &Version::new(2, 2, 3)));
+		assert!(SchemaDiff::Major.allows(&server, &Version::new(0, 1, 0)));
+	}
+
+	#[test]
+	fn parse_schema_diff(){
+		let diff: SchemaDiff = serde_yaml::from_str("minor").unwrap();
+		assert_eq!(diff,SchemaDiff::Minor);
+		assert!(serde_yaml::from_str::<SchemaDiff>("small").is_err());
+	}
+}
@@ -0,0 +1,31 @@
+use compiler::diff;
+use core_types::ApiSchema;
+
+/// Compare two schema files
+/// 
+/// `cargo run --example diff -- old.yml new.yml`
+fn main() -> Result<(),compiler::Error>{
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	let [old,new] = args.as_slice() else {
+		eprintln!("usage: diff <old schema> <new schema>");
+		std::process::exit(2);
+	};
+
+	let (old,_) = ApiSchema::load(old)?;
+	let (new,_) = ApiSchema::load(new)?;
+	let changes = diff::diff(&old, &new);
+
+	for change in &changes.changes{
+		println!("{}",change);
+	}
+	println!("required version bump: {}",changes.required_bump());
+
+	if let Err(errors) = changes.check(&old, &new){
+		for error in errors{
+			eprintln!("error: {}",error);
+		}
+		std::process::exit(1);
+	}
+
+	Ok(())
+}
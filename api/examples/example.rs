@@ -1,10 +1,8 @@
 use compiler::ts;
 
 fn main() -> Result<(),compiler::Error>{
-	ts::codegen(
-		"examples/schema.yml",
-		"../typescript-client/types.ts"
-	)?;
+	let output = ts::generate("examples/schema.yml")?;
+	println!("Generated {}",output.display());
 	
 	Ok(())
 }
\ No newline at end of file
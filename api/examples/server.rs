@@ -1,12 +1,36 @@
-use axum::{routing::get, Router};
-use compiler::ValidationLayer;
+use compiler::rs::code_gen;
 
+code_gen!("schema.yml");
+
+#[derive(Clone)]
+struct Server;
+
+impl Api for Server{
+	async fn create_user(&self, payload: UserPayload) -> Result<User,CreateUserError>{
+		if payload.id.is_empty(){
+			return Err(CreateUserError::Conflict(Error{
+				description: String::from("User already exists"),
+				details: String::from("A user without an id already exists"),
+				code: 409
+			}));
+		}
+		
+		Ok(User{
+			id: 1,
+			name: String::from("Jane"),
+			email: format!("{}@example.com",payload.id),
+			created_at: String::from("2025-01-01")
+		})
+	}
+
+	async fn submit_order(&self, payload: OrderPayload) -> String{
+		payload.cart_id
+	}
+}
 
 #[tokio::main]
 async fn main(){
-	let router = Router::new()
-		.route("/", get(||async {"Hello world"}))
-		.layer(ValidationLayer::new("123abc"));
+	let router = router(Server);
 	
 	println!("Starting sever on port 1000");
 	let listener = tokio::net::TcpListener::bind("0.0.0.0:1000")
@@ -16,4 +40,4 @@ async fn main(){
 	axum::serve(listener, router)
 		.await
 		.unwrap();
-}
\ No newline at end of file
+}